@@ -0,0 +1,362 @@
+use std::collections::HashMap;
+
+use rand::{RngCore, SeedableRng};
+use rand_chacha::ChaCha20Rng;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::steam::SteamId;
+
+/// Seed that is generated before the draw. Only its hash is announced until the draw is done.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct DrawCommitment {
+    /// hex encoded 32 bytes
+    pub seed: String,
+    /// hex encoded SHA-256 of the seed bytes
+    pub seed_hash: String,
+}
+
+impl DrawCommitment {
+    pub fn new() -> Self {
+        let mut seed = [0u8; 32];
+        rand::rng().fill_bytes(&mut seed);
+        Self {
+            seed: to_hex(&seed),
+            seed_hash: to_hex(&Sha256::digest(seed)),
+        }
+    }
+
+    /// `weights` are taken in account only if specified, otherwise draw is uniform
+    pub fn draw(
+        self,
+        candidates: &[SteamId],
+        weights: Option<&HashMap<SteamId, f64>>,
+        winners_count: usize,
+    ) -> DrawProof {
+        let candidates = sorted_candidates(candidates);
+        let weights = weights.map(|weights| {
+            candidates
+                .iter()
+                .map(|id| weights.get(id).copied().unwrap_or_default())
+                .collect::<Vec<f64>>()
+        });
+        let winners = draw_with_seed(&self.seed, &candidates, weights.as_deref(), winners_count)
+            .unwrap_or_default();
+        DrawProof {
+            seed: self.seed,
+            seed_hash: self.seed_hash,
+            candidates_hash: candidates_hash(&candidates, weights.as_deref()),
+            winners_count,
+            candidates,
+            weights,
+            winners,
+            replaced: vec![],
+        }
+    }
+}
+
+/// Everything that is needed to re-run the draw and get the same winners.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct DrawProof {
+    pub seed: String,
+    pub seed_hash: String,
+    /// hex encoded SHA-256 of the sorted candidates ids joined with `,`,
+    /// for weighted draw followed by `;` and their weights joined with `,`
+    pub candidates_hash: String,
+    pub winners_count: usize,
+    /// sorted ascending
+    pub candidates: Vec<SteamId>,
+    /// weight of each candidate in the same order, absent for uniform draw
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub weights: Option<Vec<f64>>,
+    /// in draw order, including who was replaced by re-roll
+    pub winners: Vec<SteamId>,
+    /// who was replaced by re-roll with the next drawn winner
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub replaced: Vec<SteamId>,
+}
+
+#[derive(Debug)]
+pub enum DrawProofError {
+    InvalidSeed,
+    InvalidWeights,
+    SeedHashMismatch,
+    CandidatesHashMismatch,
+    WinnersMismatch,
+}
+
+impl DrawProof {
+    /// Draws one more winner with the same seed. Previous winners stay the same,
+    /// because the draw is done pick by pick.
+    pub fn draw_next(&mut self) -> Option<SteamId> {
+        let winners = draw_with_seed(
+            &self.seed,
+            &self.candidates,
+            self.weights.as_deref(),
+            self.winners_count + 1,
+        )?;
+        if winners.len() <= self.winners.len() {
+            return None;
+        }
+        self.winners_count += 1;
+        self.winners = winners;
+        self.winners.last().copied()
+    }
+
+    pub fn verify(&self) -> Result<(), DrawProofError> {
+        let seed = from_hex(&self.seed).ok_or(DrawProofError::InvalidSeed)?;
+        if to_hex(&Sha256::digest(seed)) != self.seed_hash {
+            return Err(DrawProofError::SeedHashMismatch);
+        }
+        if candidates_hash(&self.candidates, self.weights.as_deref()) != self.candidates_hash {
+            return Err(DrawProofError::CandidatesHashMismatch);
+        }
+        if let Some(ref weights) = self.weights {
+            if weights.len() != self.candidates.len()
+                || weights
+                    .iter()
+                    .any(|weight| !weight.is_finite() || *weight < 0.)
+            {
+                return Err(DrawProofError::InvalidWeights);
+            }
+        }
+        let winners = draw_with_seed(
+            &self.seed,
+            &self.candidates,
+            self.weights.as_deref(),
+            self.winners_count,
+        )
+        .ok_or(DrawProofError::InvalidSeed)?;
+        if winners != self.winners {
+            return Err(DrawProofError::WinnersMismatch);
+        }
+        Ok(())
+    }
+}
+
+/// Uniform draw is partial Fisher-Yates shuffle of sorted candidates with ChaCha20 seeded by the seed.
+/// On step `i` element `i` is swapped with element `i + next_u64() % (len - i)`.
+///
+/// Weighted draw picks one by one: on each pick `r = (next_u64() >> 11) / 2^53 * total weight`
+/// of not yet picked candidates, and the first candidate whose cumulative weight exceeds `r` wins.
+fn draw_with_seed(
+    seed: &str,
+    candidates: &[SteamId],
+    weights: Option<&[f64]>,
+    winners_count: usize,
+) -> Option<Vec<SteamId>> {
+    let seed = from_hex(seed)?;
+    let mut rng = ChaCha20Rng::from_seed(seed);
+    let winners_count = winners_count.min(candidates.len());
+
+    if let Some(weights) = weights {
+        let mut weights = weights.to_vec();
+        let mut winners = Vec::with_capacity(winners_count);
+        for _ in 0..winners_count {
+            let total: f64 = weights.iter().sum();
+            if total <= 0. {
+                break;
+            }
+            let r = (rng.next_u64() >> 11) as f64 / (1u64 << 53) as f64 * total;
+            let mut cumulative = 0.;
+            let mut picked = None;
+            for (i, weight) in weights.iter().enumerate() {
+                if *weight <= 0. {
+                    continue;
+                }
+                cumulative += weight;
+                picked = Some(i);
+                if cumulative > r {
+                    break;
+                }
+            }
+            let picked = picked?;
+            weights[picked] = 0.;
+            winners.push(candidates[picked]);
+        }
+        return Some(winners);
+    }
+
+    let mut candidates = candidates.to_vec();
+    for i in 0..winners_count {
+        let j = i + (rng.next_u64() % (candidates.len() - i) as u64) as usize;
+        candidates.swap(i, j);
+    }
+    candidates.truncate(winners_count);
+    Some(candidates)
+}
+
+#[inline]
+fn sorted_candidates(candidates: &[SteamId]) -> Vec<SteamId> {
+    let mut candidates = candidates.to_vec();
+    candidates.sort_unstable_by_key(|id| id.0);
+    candidates.dedup();
+    candidates
+}
+
+/// Weights are formatted as the shortest strings which parse back to the same values
+fn candidates_hash(sorted_candidates: &[SteamId], weights: Option<&[f64]>) -> String {
+    let mut joined = sorted_candidates
+        .iter()
+        .map(ToString::to_string)
+        .collect::<Vec<String>>()
+        .join(",");
+    if let Some(weights) = weights {
+        joined.push(';');
+        joined += &weights
+            .iter()
+            .map(ToString::to_string)
+            .collect::<Vec<String>>()
+            .join(",");
+    }
+    to_hex(&Sha256::digest(joined.as_bytes()))
+}
+
+#[inline]
+fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{b:02x}")).collect()
+}
+
+fn from_hex(hex: &str) -> Option<[u8; 32]> {
+    if hex.len() != 64 || !hex.is_ascii() {
+        return None;
+    }
+    let mut bytes = [0u8; 32];
+    for (i, byte) in bytes.iter_mut().enumerate() {
+        *byte = u8::from_str_radix(&hex[i * 2..i * 2 + 2], 16).ok()?;
+    }
+    Some(bytes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn candidates() -> Vec<SteamId> {
+        (0..50).map(|n| SteamId(76561198000000000 + n)).collect()
+    }
+
+    fn weights() -> HashMap<SteamId, f64> {
+        candidates()
+            .into_iter()
+            .enumerate()
+            .map(|(i, id)| (id, (i % 5) as f64 * 0.5))
+            .collect()
+    }
+
+    /// Pins the draw to the algorithm described on `draw_with_seed`,
+    /// so proofs published before keep verifying
+    #[test]
+    fn known_answer() {
+        let commitment = DrawCommitment {
+            seed: "00".repeat(32),
+            seed_hash: "66687aadf862bd776c8fc18b8e9f8e20089714856ee233b3902a591d0d5f2925"
+                .to_owned(),
+        };
+
+        let uniform = commitment.clone().draw(&candidates(), None, 5);
+        assert_eq!(
+            uniform.candidates_hash,
+            "0331b51703a635480eb550cfa81431242fa0bb5ad3d19c40158881fc9a5f7795"
+        );
+        assert_eq!(
+            uniform.winners,
+            [30, 7, 31, 5, 0].map(|n| SteamId(76561198000000000 + n))
+        );
+        assert!(uniform.verify().is_ok());
+
+        let weighted = commitment.draw(&candidates(), Some(&weights()), 5);
+        assert_eq!(
+            weighted.candidates_hash,
+            "a97ee57c18c2feded90e064ef9438c3ac40b5a70d6d7b628e9ecc6de91fa2980"
+        );
+        assert_eq!(
+            weighted.winners,
+            [29, 8, 4, 41, 28].map(|n| SteamId(76561198000000000 + n))
+        );
+        assert!(weighted.verify().is_ok());
+    }
+
+    #[test]
+    fn same_seed_draws_same_winners() {
+        let commitment = DrawCommitment::new();
+        let uniform = commitment.clone().draw(&candidates(), None, 5);
+        let mut shuffled = candidates();
+        shuffled.reverse();
+        let again = commitment.clone().draw(&shuffled, None, 5);
+        assert_eq!(uniform.winners.len(), 5);
+        assert_eq!(uniform.winners, again.winners);
+
+        let weights = weights();
+        let weighted = commitment.clone().draw(&candidates(), Some(&weights), 5);
+        let again = commitment.draw(&candidates(), Some(&weights), 5);
+        assert_eq!(weighted.winners, again.winners);
+    }
+
+    #[test]
+    fn verify_accepts_genuine_proof() {
+        let weights = weights();
+        for weights in [None, Some(&weights)] {
+            let proof = DrawCommitment::new().draw(&candidates(), weights, 3);
+            assert!(proof.verify().is_ok());
+        }
+    }
+
+    #[test]
+    fn verify_rejects_tampered_proof() {
+        let proof = DrawCommitment::new().draw(&candidates(), None, 3);
+
+        let mut tampered = proof.clone();
+        tampered.winners[0] = tampered
+            .candidates
+            .iter()
+            .copied()
+            .find(|id| !proof.winners.contains(id))
+            .unwrap();
+        assert!(matches!(
+            tampered.verify(),
+            Err(DrawProofError::WinnersMismatch)
+        ));
+
+        let mut tampered = proof.clone();
+        tampered.candidates.pop();
+        assert!(matches!(
+            tampered.verify(),
+            Err(DrawProofError::CandidatesHashMismatch)
+        ));
+
+        let mut tampered = proof.clone();
+        tampered.seed = DrawCommitment::new().seed;
+        assert!(matches!(
+            tampered.verify(),
+            Err(DrawProofError::SeedHashMismatch)
+        ));
+
+        let weights = weights();
+        let mut tampered = DrawCommitment::new().draw(&candidates(), Some(&weights), 3);
+        tampered.weights.as_mut().unwrap()[0] += 1.;
+        assert!(matches!(
+            tampered.verify(),
+            Err(DrawProofError::CandidatesHashMismatch)
+        ));
+    }
+
+    #[test]
+    fn draw_next_never_returns_existing_winner() {
+        let weights = weights();
+        for weights in [None, Some(&weights)] {
+            let mut proof = DrawCommitment::new().draw(&candidates(), weights, 3);
+            let first_winners = proof.winners.clone();
+            while let Some(next) = proof.draw_next() {
+                assert!(!proof.winners[..proof.winners.len() - 1].contains(&next));
+                assert_eq!(proof.winners[..first_winners.len()], first_winners);
+            }
+            let drawable = match weights {
+                Some(weights) => weights.values().filter(|weight| **weight > 0.).count(),
+                None => candidates().len(),
+            };
+            assert_eq!(proof.winners.len(), drawable);
+            assert!(proof.verify().is_ok());
+        }
+    }
+}
@@ -1,19 +1,43 @@
-use chrono::Utc;
+use serde::{Deserialize, Serialize};
 
 use crate::steam::SteamUser;
 
-use super::Filters;
+use super::{FilterContext, FriendFilter};
 
-pub fn apply_account_age_filter(friends: &mut Vec<SteamUser>, filters: &Filters) {
-    if filters.account_age <= 0 && !filters.exclude_unknown_age {
-        return;
+#[derive(Debug, Default, Clone, Copy, Serialize, Deserialize)]
+pub struct AccountAgeFilter {
+    pub min_years: u32,
+    pub exclude_unknown: bool,
+}
+
+impl FriendFilter for AccountAgeFilter {
+    fn is_active(&self, _context: &FilterContext) -> bool {
+        self.min_years > 0 || self.exclude_unknown
     }
 
-    let now = Utc::now();
-    friends.retain(|friend| {
+    fn matches(&self, friend: &SteamUser, context: &FilterContext) -> bool {
         friend
             .created_at
-            .map(|created_at| now.years_since(created_at).unwrap() >= filters.account_age)
-            .unwrap_or(!filters.exclude_unknown_age)
-    });
+            .map(|created_at| context.now.years_since(created_at).unwrap() >= self.min_years)
+            .unwrap_or(!self.exclude_unknown)
+    }
+
+    fn name(&self) -> String {
+        if self.exclude_unknown {
+            format!("account age >= {} (known)", self.min_years)
+        } else {
+            format!("account age >= {}", self.min_years)
+        }
+    }
+
+    fn explain(&self, friend: &SteamUser, context: &FilterContext) -> String {
+        match friend.created_at {
+            Some(created_at) => {
+                let years = context.now.years_since(created_at).unwrap();
+                let sign = if years >= self.min_years { ">=" } else { "<" };
+                format!("account age {years}y {sign} {}y", self.min_years)
+            }
+            None => "account age is unknown".to_owned(),
+        }
+    }
 }
@@ -0,0 +1,147 @@
+use serde::{Deserialize, Serialize};
+
+use crate::steam::{RecentPlaytime, SteamUser};
+
+use super::{FilterContext, FriendFilter};
+
+/// Who is online now or was online in last N days, 0 is off.
+/// Friends who hide last online time pass.
+#[derive(Debug, Default, Clone, Copy, Serialize, Deserialize)]
+pub struct OnlineWithin(pub u32);
+
+impl FriendFilter for OnlineWithin {
+    fn is_active(&self, _context: &FilterContext) -> bool {
+        self.0 > 0
+    }
+
+    fn matches(&self, friend: &SteamUser, context: &FilterContext) -> bool {
+        friend.is_online()
+            || friend
+                .last_logoff
+                .map(|last_logoff| (context.now - last_logoff).num_days() < self.0 as i64)
+                .unwrap_or(true)
+    }
+
+    fn name(&self) -> String {
+        format!("online in last {}d", self.0)
+    }
+
+    fn explain(&self, friend: &SteamUser, context: &FilterContext) -> String {
+        if friend.is_online() {
+            return "online now".to_owned();
+        }
+        match friend.last_logoff {
+            Some(last_logoff) => {
+                format!(
+                    "last online {}d ago",
+                    (context.now - last_logoff).num_days()
+                )
+            }
+            None => "last online is unknown".to_owned(),
+        }
+    }
+}
+
+/// Status of friends when they were fetched, inactive if it is outdated
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum PresenceFilter {
+    #[default]
+    Any,
+    Online,
+    InGame,
+}
+
+impl FriendFilter for PresenceFilter {
+    fn is_active(&self, context: &FilterContext) -> bool {
+        *self != Self::Any && context.presence_is_fresh
+    }
+
+    fn matches(&self, friend: &SteamUser, _context: &FilterContext) -> bool {
+        match self {
+            Self::Any => true,
+            Self::Online => friend.is_online(),
+            Self::InGame => friend.is_in_game(),
+        }
+    }
+
+    fn name(&self) -> String {
+        match self {
+            Self::Any => "any status",
+            Self::Online => "online",
+            Self::InGame => "in game",
+        }
+        .to_owned()
+    }
+
+    fn explain(&self, friend: &SteamUser, _context: &FilterContext) -> String {
+        match friend.playing_game {
+            Some(ref game) => format!("in game {game}"),
+            None => friend.persona_state.to_string().to_lowercase(),
+        }
+    }
+}
+
+/// Who played any game in last 2 weeks or at least N hours in total
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct RecentActivityFilter {
+    pub enabled: bool,
+    /// Total hours in all games, 0 requires any playtime
+    pub min_hours: u32,
+    /// Keep who hides games with privacy settings or whose games are not loaded
+    pub include_unknown: bool,
+}
+
+impl Default for RecentActivityFilter {
+    #[inline]
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            min_hours: 0,
+            include_unknown: true,
+        }
+    }
+}
+
+impl FriendFilter for RecentActivityFilter {
+    fn is_active(&self, _context: &FilterContext) -> bool {
+        self.enabled
+    }
+
+    fn matches(&self, friend: &SteamUser, context: &FilterContext) -> bool {
+        match context.recent_playtime.get(&friend.id) {
+            Some(Some(RecentPlaytime::Minutes(minutes))) if self.min_hours == 0 => *minutes > 0,
+            Some(Some(RecentPlaytime::Minutes(minutes))) => *minutes >= self.min_hours * 60,
+            None if context.missing_on_demand_passes => true,
+            _ => self.include_unknown,
+        }
+    }
+
+    fn name(&self) -> String {
+        let name = if self.min_hours == 0 {
+            "played in last 2 weeks".to_owned()
+        } else {
+            format!("played >= {}h in last 2 weeks", self.min_hours)
+        };
+        if self.include_unknown {
+            name
+        } else {
+            format!("{name} (known)")
+        }
+    }
+
+    fn explain(&self, friend: &SteamUser, context: &FilterContext) -> String {
+        match context.recent_playtime.get(&friend.id) {
+            Some(Some(RecentPlaytime::Minutes(minutes))) => {
+                format!("played {:.1}h in last 2 weeks", *minutes as f32 / 60.)
+            }
+            Some(Some(RecentPlaytime::Private)) => "recent games are private".to_owned(),
+            Some(None) => "recent games failed to load".to_owned(),
+            None => "recent games are not loaded yet".to_owned(),
+        }
+    }
+
+    #[inline]
+    fn uses_loaded_on_demand(&self) -> bool {
+        self.enabled
+    }
+}
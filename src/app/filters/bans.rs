@@ -0,0 +1,109 @@
+use serde::{Deserialize, Serialize};
+
+use crate::steam::SteamUser;
+
+use super::{FilterContext, FriendFilter};
+
+/// Whose bans were loaded, others can be excluded before ban filters
+/// which do not consider them banned
+pub struct KnownBans;
+
+impl FriendFilter for KnownBans {
+    fn matches(&self, friend: &SteamUser, _context: &FilterContext) -> bool {
+        friend.bans.is_some()
+    }
+
+    fn name(&self) -> String {
+        "bans known".to_owned()
+    }
+
+    fn explain(&self, friend: &SteamUser, _context: &FilterContext) -> String {
+        if friend.bans.is_some() {
+            "bans are known".to_owned()
+        } else {
+            "bans are unknown".to_owned()
+        }
+    }
+}
+
+/// Who is banned in Steam Community.
+/// Friends whose bans failed to load are not considered banned, see `KnownBans`.
+pub struct CommunityBanned;
+
+impl FriendFilter for CommunityBanned {
+    fn matches(&self, friend: &SteamUser, _context: &FilterContext) -> bool {
+        friend
+            .bans
+            .as_ref()
+            .is_some_and(|bans| bans.community_banned)
+    }
+
+    fn name(&self) -> String {
+        "community banned".to_owned()
+    }
+
+    fn explain(&self, friend: &SteamUser, _context: &FilterContext) -> String {
+        match friend.bans {
+            Some(ref bans) if bans.community_banned => "community banned".to_owned(),
+            Some(_) => "not community banned".to_owned(),
+            None => "bans are unknown".to_owned(),
+        }
+    }
+}
+
+/// Who is banned or on probation in Steam Community Market and trading
+pub struct TradeBanned;
+
+impl FriendFilter for TradeBanned {
+    fn matches(&self, friend: &SteamUser, _context: &FilterContext) -> bool {
+        friend
+            .bans
+            .as_ref()
+            .is_some_and(|bans| bans.is_trade_banned())
+    }
+
+    fn name(&self) -> String {
+        "trade banned".to_owned()
+    }
+
+    fn explain(&self, friend: &SteamUser, _context: &FilterContext) -> String {
+        match friend.bans {
+            Some(ref bans) if bans.is_trade_banned() => format!("trade ban: {}", bans.economy_ban),
+            Some(_) => "not trade banned".to_owned(),
+            None => "bans are unknown".to_owned(),
+        }
+    }
+}
+
+/// Who got VAC ban in last N days, 0 is off.
+/// Days are counted since the last ban of any type, so game bans may make VAC ban look recent.
+#[derive(Debug, Default, Clone, Copy, Serialize, Deserialize)]
+pub struct VacBannedWithin(pub u32);
+
+impl FriendFilter for VacBannedWithin {
+    fn is_active(&self, _context: &FilterContext) -> bool {
+        self.0 > 0
+    }
+
+    fn matches(&self, friend: &SteamUser, _context: &FilterContext) -> bool {
+        friend
+            .bans
+            .as_ref()
+            .is_some_and(|bans| bans.vac_banned && bans.days_since_last_ban < self.0)
+    }
+
+    fn name(&self) -> String {
+        format!("VAC banned in last {}d", self.0)
+    }
+
+    fn explain(&self, friend: &SteamUser, _context: &FilterContext) -> String {
+        match friend.bans {
+            Some(ref bans) if bans.vac_banned => format!(
+                "{} VAC ban(s), last ban {}d ago",
+                bans.vac_bans, bans.days_since_last_ban
+            ),
+            Some(_) => "no VAC bans".to_owned(),
+            None => "bans are unknown".to_owned(),
+        }
+    }
+}
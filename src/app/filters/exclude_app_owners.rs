@@ -0,0 +1,31 @@
+use crate::steam::SteamUser;
+
+use super::{FilterContext, FriendFilter, giveaway_app_name};
+
+/// Who already owns app for giveaway
+pub struct GiveawayAppOwners;
+
+impl FriendFilter for GiveawayAppOwners {
+    fn is_active(&self, context: &FilterContext) -> bool {
+        context.app_user_details.is_some()
+    }
+
+    fn matches(&self, friend: &SteamUser, context: &FilterContext) -> bool {
+        context
+            .app_user_details
+            .map(|details| details.is_owned_by(friend.id))
+            .unwrap_or_default()
+    }
+
+    fn name(&self) -> String {
+        "owns app for giveaway".to_owned()
+    }
+
+    fn explain(&self, friend: &SteamUser, context: &FilterContext) -> String {
+        if self.matches(friend, context) {
+            format!("in friendsown for {}", giveaway_app_name(context))
+        } else {
+            format!("not in friendsown for {}", giveaway_app_name(context))
+        }
+    }
+}
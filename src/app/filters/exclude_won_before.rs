@@ -1,6 +1,116 @@
-use crate::{app::Winners, steam::SteamUser};
+use std::collections::HashSet;
+
+use chrono::{TimeDelta, Utc};
+
+use crate::{
+    app::Winners,
+    steam::{SteamId, SteamUser},
+};
+
+use super::{FilterContext, FriendFilter};
+
+/// Who won in any saved giveaway
+pub struct WonBefore;
+
+impl FriendFilter for WonBefore {
+    fn matches(&self, friend: &SteamUser, context: &FilterContext) -> bool {
+        context.winners.all_time.contains_key(&friend.id)
+    }
+
+    fn name(&self) -> String {
+        "won before".to_owned()
+    }
+
+    fn explain(&self, friend: &SteamUser, context: &FilterContext) -> String {
+        wins_explanation(friend, context)
+    }
+}
 
 #[inline]
-pub fn exclude_who_won_before(friends: &mut Vec<SteamUser>, winners: &Winners) {
-    friends.retain(|friend| !winners.all_time.contains_key(&friend.id));
+fn wins_explanation(friend: &SteamUser, context: &FilterContext) -> String {
+    match context.winners.all_time.get(&friend.id) {
+        Some(wins) => format!("won {wins} time(s) before"),
+        None => "never won before".to_owned(),
+    }
+}
+
+/// Who won at least N times
+pub struct WonAtLeast(pub usize);
+
+impl FriendFilter for WonAtLeast {
+    fn is_active(&self, _context: &FilterContext) -> bool {
+        self.0 > 0
+    }
+
+    fn matches(&self, friend: &SteamUser, context: &FilterContext) -> bool {
+        context
+            .winners
+            .all_time
+            .get(&friend.id)
+            .map(|wins| *wins >= self.0)
+            .unwrap_or_default()
+    }
+
+    fn name(&self) -> String {
+        format!("won >= {} times", self.0)
+    }
+
+    fn explain(&self, friend: &SteamUser, context: &FilterContext) -> String {
+        wins_explanation(friend, context)
+    }
+}
+
+/// Who won in last days or giveaways of win limit filters
+pub struct WonRecently;
+
+impl FriendFilter for WonRecently {
+    fn is_active(&self, context: &FilterContext) -> bool {
+        context.recent_winners.is_some()
+    }
+
+    fn matches(&self, friend: &SteamUser, context: &FilterContext) -> bool {
+        context
+            .recent_winners
+            .as_ref()
+            .map(|recent_winners| recent_winners.contains(&friend.id))
+            .unwrap_or_default()
+    }
+
+    fn name(&self) -> String {
+        "won recently".to_owned()
+    }
+
+    fn explain(&self, friend: &SteamUser, context: &FilterContext) -> String {
+        if self.matches(friend, context) {
+            "won in recent days or giveaways".to_owned()
+        } else {
+            "did not win in recent days or giveaways".to_owned()
+        }
+    }
+}
+
+/// Returns `None` if both `within_days` and `within_giveaways` are off
+pub fn recent_winners(
+    winners: &Winners,
+    within_days: u32,
+    within_giveaways: usize,
+) -> Option<HashSet<SteamId>> {
+    if within_days == 0 && within_giveaways == 0 {
+        return None;
+    }
+
+    let since = Utc::now() - TimeDelta::days(within_days as i64);
+    let recent_giveaways_from = winners.history.len().saturating_sub(within_giveaways);
+    Some(
+        winners
+            .history
+            .iter()
+            .enumerate()
+            .filter(|(i, record)| {
+                (within_days > 0 && record.date >= since)
+                    || (within_giveaways > 0 && *i >= recent_giveaways_from)
+            })
+            .flat_map(|(_, record)| record.winners.iter().map(|winner| winner.id))
+            .collect(),
+    )
 }
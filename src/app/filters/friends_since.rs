@@ -0,0 +1,44 @@
+use serde::{Deserialize, Serialize};
+
+use crate::steam::SteamUser;
+
+use super::{FilterContext, FriendFilter};
+
+/// Excludes who became a friend recently, e.g. right before a giveaway
+#[derive(Debug, Default, Clone, Copy, Serialize, Deserialize)]
+pub struct FriendsSinceFilter {
+    pub min_days: u32,
+}
+
+impl FriendFilter for FriendsSinceFilter {
+    fn is_active(&self, _context: &FilterContext) -> bool {
+        self.min_days > 0
+    }
+
+    /// Friends with unknown date pass, e.g. loaded from old disk cache
+    fn matches(&self, friend: &SteamUser, context: &FilterContext) -> bool {
+        friend
+            .friend_since
+            .map(|since| (context.now - since).num_days() >= self.min_days as i64)
+            .unwrap_or(true)
+    }
+
+    fn name(&self) -> String {
+        format!("friends for >= {}d", self.min_days)
+    }
+
+    fn explain(&self, friend: &SteamUser, context: &FilterContext) -> String {
+        match friend.friend_since {
+            Some(since) => {
+                let days = (context.now - since).num_days();
+                let sign = if days >= self.min_days as i64 {
+                    ">="
+                } else {
+                    "<"
+                };
+                format!("friends for {days}d {sign} {}d", self.min_days)
+            }
+            None => "friends since unknown date".to_owned(),
+        }
+    }
+}
@@ -1,29 +1,58 @@
-use std::hash::Hash;
+use std::{collections::HashMap, hash::Hash};
 
-use crate::steam::{SteamStoreItem, SteamUser};
+use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Default)]
+use crate::steam::{PlayerAchievements, SteamId, SteamStoreItem, SteamUser, StoreItemId};
+
+use super::{FilterContext, FriendFilter};
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum HasAppMode {
+    #[default]
+    Owns,
+    /// Playtime bounds and achievements are not used
+    NotOwns,
+}
+
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
 pub struct HasAppFilter {
     pub app: Option<SteamStoreItem>,
-    // achievement_percent: u8,
+    #[serde(default)]
+    pub mode: HasAppMode,
+    /// Min percent of unlocked achievements, 0 disables it
+    #[serde(default)]
+    pub achievement_percent: u8,
+    /// Keep who hides achievements with privacy settings
+    /// or whose achievements are unknown
+    #[serde(default)]
+    pub include_private_achievements: bool,
     pub playtime_twoweeks: u16, // in hours
     pub playtime_total: u32,    // in hours
+    /// exclusive
+    #[serde(default)]
+    pub max_playtime_twoweeks: Option<u16>, // in hours
+    /// exclusive
+    #[serde(default)]
+    pub max_playtime_total: Option<u32>, // in hours
 
+    #[serde(skip)]
     pub is_loading: bool,
 }
 
 impl PartialEq for HasAppFilter {
     fn eq(&self, other: &Self) -> bool {
-        self.app
-            .as_ref()
-            .map(|app| {
-                other
-                    .app
-                    .as_ref()
-                    .map(|other_app| app.id == other_app.id)
-                    .unwrap_or_default()
-            })
-            .unwrap_or_default()
+        self.mode == other.mode
+            && self
+                .app
+                .as_ref()
+                .map(|app| {
+                    other
+                        .app
+                        .as_ref()
+                        .map(|other_app| app.id == other_app.id)
+                        .unwrap_or_default()
+                })
+                .unwrap_or_default()
     }
 }
 
@@ -36,46 +65,126 @@ impl Hash for HasAppFilter {
             .map(|app| app.id)
             .unwrap_or_default()
             .hash(state);
+        self.mode.hash(state);
     }
 }
 
-pub fn apply_has_app_filters(friends: &mut Vec<SteamUser>, filters: &[HasAppFilter]) {
-    if filters.is_empty() {
-        return;
-    }
+pub type AchievementsOfFriends = HashMap<StoreItemId, HashMap<SteamId, PlayerAchievements>>;
 
-    let filters = filters
-        .iter()
-        .filter(|f| {
-            !f.is_loading
-                && f.app
-                    .as_ref()
-                    .map(|app| app.user_details.is_some())
-                    .unwrap_or_default()
-        })
-        .collect::<Vec<&HasAppFilter>>();
-
-    if filters.is_empty() {
-        return;
+impl HasAppFilter {
+    #[inline]
+    pub fn uses_achievements(&self) -> bool {
+        self.achievement_percent > 0 && self.mode == HasAppMode::Owns
     }
 
-    friends.retain(|friend| {
-        for filter in &filters {
-            let app_user_details = filter.app.as_ref().unwrap().user_details.as_ref().unwrap();
-            let friend_own_option = app_user_details
-                .friends_own
-                .iter()
-                .find(|fo| fo.id == friend.id);
-            if let Some(friend_own) = friend_own_option {
-                if friend_own.playtime_total < filter.playtime_total * 60
-                    || friend_own.playtime_twoweeks < filter.playtime_twoweeks * 60
+    /// `Ok` with explanation if friend passes the filter, otherwise `Err` with reason
+    fn check(&self, friend: &SteamUser, context: &FilterContext) -> Result<String, String> {
+        let app = self.app.as_ref().unwrap();
+        let app_user_details = app.user_details.as_ref().unwrap();
+        let friend_own_option = app_user_details.friends_own.get(&friend.id);
+        match (self.mode, friend_own_option) {
+            (HasAppMode::Owns, Some(friend_own)) => {
+                let total = friend_own.playtime_total / 60;
+                let twoweeks = friend_own.playtime_twoweeks / 60;
+                if friend_own.playtime_total < self.playtime_total * 60 {
+                    return Err(format!(
+                        "playtime total {total}h < {}h for app {}",
+                        self.playtime_total, app.id
+                    ));
+                }
+                if friend_own.playtime_twoweeks < self.playtime_twoweeks * 60 {
+                    return Err(format!(
+                        "playtime 2 weeks {twoweeks}h < {}h for app {}",
+                        self.playtime_twoweeks, app.id
+                    ));
+                }
+                if let Some(max) = self
+                    .max_playtime_total
+                    .filter(|max| friend_own.playtime_total >= max * 60)
                 {
-                    return false;
+                    return Err(format!(
+                        "playtime total {total}h >= {max}h for app {}",
+                        app.id
+                    ));
+                }
+                if let Some(max) = self
+                    .max_playtime_twoweeks
+                    .filter(|max| friend_own.playtime_twoweeks >= max * 60)
+                {
+                    return Err(format!(
+                        "playtime 2 weeks {twoweeks}h >= {max}h for app {}",
+                        app.id
+                    ));
+                }
+                if self.achievement_percent > 0 {
+                    match context
+                        .achievements
+                        .get(&app.id)
+                        .and_then(|friends| friends.get(&friend.id))
+                    {
+                        Some(PlayerAchievements::Completion(percent))
+                            if *percent < self.achievement_percent as f32 =>
+                        {
+                            return Err(format!(
+                                "achievements {percent:.0}% < {}% for app {}",
+                                self.achievement_percent, app.id
+                            ));
+                        }
+                        Some(PlayerAchievements::Completion(_)) => {}
+                        None if context.missing_on_demand_passes => {}
+                        Some(PlayerAchievements::Private | PlayerAchievements::NoStats) | None
+                            if !self.include_private_achievements =>
+                        {
+                            return Err(format!("achievements are unknown for app {}", app.id));
+                        }
+                        _ => {}
+                    }
                 }
-            } else {
-                return false;
+                Ok(format!("in friendsown for app {}", app.id))
             }
+            (HasAppMode::Owns, None) => Err(format!("not in friendsown for app {}", app.id)),
+            (HasAppMode::NotOwns, Some(_)) => Err(format!("in friendsown for app {}", app.id)),
+            (HasAppMode::NotOwns, None) => Ok(format!("not in friendsown for app {}", app.id)),
         }
-        true
-    });
+    }
+}
+
+impl FriendFilter for HasAppFilter {
+    fn is_active(&self, _context: &FilterContext) -> bool {
+        !self.is_loading
+            && self
+                .app
+                .as_ref()
+                .map(|app| app.user_details.is_some())
+                .unwrap_or_default()
+    }
+
+    #[inline]
+    fn matches(&self, friend: &SteamUser, context: &FilterContext) -> bool {
+        self.check(friend, context).is_ok()
+    }
+
+    fn name(&self) -> String {
+        format!(
+            "{} {}",
+            match self.mode {
+                HasAppMode::Owns => "owns",
+                HasAppMode::NotOwns => "does not own",
+            },
+            self.app
+                .as_ref()
+                .map(|app| app.name.as_str())
+                .unwrap_or("?")
+        )
+    }
+
+    #[inline]
+    fn explain(&self, friend: &SteamUser, context: &FilterContext) -> String {
+        self.check(friend, context).unwrap_or_else(|reason| reason)
+    }
+
+    #[inline]
+    fn uses_loaded_on_demand(&self) -> bool {
+        self.uses_achievements()
+    }
 }
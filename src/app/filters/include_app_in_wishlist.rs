@@ -1,16 +1,87 @@
-use crate::steam::{SteamUser, StoreItemUserDetails};
+use serde::{Deserialize, Serialize};
+
+use crate::steam::{SteamStoreItem, SteamUser};
+
+use super::{FilterContext, FriendFilter, giveaway_app_name};
+
+/// Who has app for giveaway in wishlist
+pub struct GiveawayAppInWishlist;
+
+impl FriendFilter for GiveawayAppInWishlist {
+    fn is_active(&self, context: &FilterContext) -> bool {
+        context.app_user_details.is_some()
+    }
+
+    fn matches(&self, friend: &SteamUser, context: &FilterContext) -> bool {
+        context
+            .app_user_details
+            .map(|details| details.is_wanted_by(friend.id))
+            .unwrap_or_default()
+    }
+
+    fn name(&self) -> String {
+        "has app for giveaway in wishlist".to_owned()
+    }
+
+    fn explain(&self, friend: &SteamUser, context: &FilterContext) -> String {
+        wishlist_explanation(self.matches(friend, context), &giveaway_app_name(context))
+    }
+}
 
 #[inline]
-pub fn include_who_has_store_item_in_wishlist(
-    friends: &mut Vec<SteamUser>,
-    store_item_user_details: Option<&StoreItemUserDetails>,
-) {
-    if let Some(store_item_user_details) = store_item_user_details {
-        friends.retain(|friend| {
-            store_item_user_details
-                .friends_want
-                .iter()
-                .any(|f| f.id == friend.id)
-        });
+fn wishlist_explanation(in_wishlist: bool, app_name: &str) -> String {
+    if in_wishlist {
+        format!("in friendswant for {app_name}")
+    } else {
+        format!("not in friendswant for {app_name}")
+    }
+}
+
+/// Who has selected app in wishlist
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+pub struct WishlistFilter {
+    pub app: Option<SteamStoreItem>,
+
+    #[serde(skip)]
+    pub is_loading: bool,
+}
+
+impl FriendFilter for WishlistFilter {
+    fn is_active(&self, _context: &FilterContext) -> bool {
+        !self.is_loading
+            && self
+                .app
+                .as_ref()
+                .map(|app| app.user_details.is_some())
+                .unwrap_or_default()
+    }
+
+    fn matches(&self, friend: &SteamUser, _context: &FilterContext) -> bool {
+        self.app
+            .as_ref()
+            .and_then(|app| app.user_details.as_ref())
+            .map(|details| details.is_wanted_by(friend.id))
+            .unwrap_or_default()
+    }
+
+    fn name(&self) -> String {
+        format!(
+            "wishlists {}",
+            self.app
+                .as_ref()
+                .map(|app| app.name.as_str())
+                .unwrap_or("?")
+        )
+    }
+
+    fn explain(&self, friend: &SteamUser, context: &FilterContext) -> String {
+        wishlist_explanation(
+            self.matches(friend, context),
+            &self
+                .app
+                .as_ref()
+                .map(|app| format!("app {}", app.id))
+                .unwrap_or_default(),
+        )
     }
 }
@@ -1,18 +1,173 @@
 mod account_age;
+mod activity;
+mod bans;
+mod exclude_app_owners;
 mod exclude_won_before;
+mod friends_since;
 mod has_app;
 mod include_app_in_wishlist;
 mod region;
+mod steam_level;
+mod tree;
 
 pub use account_age::*;
+pub use activity::*;
+pub use bans::*;
+pub use exclude_app_owners::*;
 pub use exclude_won_before::*;
+pub use friends_since::*;
 pub use has_app::*;
 pub use include_app_in_wishlist::*;
 pub use region::*;
+pub use steam_level::*;
+pub use tree::*;
 
-use super::friends::Friends;
+use std::{
+    collections::{HashMap, HashSet},
+    sync::{Arc, RwLock, mpsc::Sender},
+    thread,
+};
 
-#[derive(Default)]
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::steam::{
+    CachedEndpoint, RecentPlaytime, SteamApiClient, SteamError, SteamId, SteamStoreItem, SteamUser,
+    StoreItemId, StoreItemUserDetails,
+};
+
+use super::{Msg, TaskToken, Winners, friends::Friends};
+
+/// Data shared by filters while filtering
+pub struct FilterContext<'a> {
+    pub now: DateTime<Utc>,
+    pub winners: &'a Winners,
+    /// `None` if win limit filters by days and giveaways are off
+    pub recent_winners: Option<HashSet<SteamId>>,
+    pub store_item_for_giveaway: Option<&'a SteamStoreItem>,
+    pub app_user_details: Option<&'a StoreItemUserDetails>,
+    pub achievements: &'a AchievementsOfFriends,
+    /// `None` if failed to load
+    pub recent_playtime: &'a HashMap<SteamId, Option<RecentPlaytime>>,
+    /// Status of friends is not filtered by if it is outdated
+    pub presence_is_fresh: bool,
+    /// Friends whose data loaded on demand is missing pass, to find whom to load it for
+    pub missing_on_demand_passes: bool,
+}
+
+impl<'a> FilterContext<'a> {
+    pub fn new(
+        filters: &Filters,
+        winners: &'a Winners,
+        store_item_for_giveaway: Option<&'a SteamStoreItem>,
+        achievements: &'a AchievementsOfFriends,
+        recent_playtime: &'a HashMap<SteamId, Option<RecentPlaytime>>,
+        presence_is_fresh: bool,
+    ) -> Self {
+        Self {
+            now: Utc::now(),
+            winners,
+            recent_winners: recent_winners(
+                winners,
+                filters.won_within_days,
+                filters.won_within_giveaways,
+            ),
+            store_item_for_giveaway,
+            app_user_details: store_item_for_giveaway.and_then(|item| item.user_details.as_ref()),
+            achievements,
+            recent_playtime,
+            presence_is_fresh,
+            missing_on_demand_passes: false,
+        }
+    }
+}
+
+pub trait FriendFilter {
+    /// Inactive filter is skipped, e.g. when it is off or its data is not loaded
+    fn is_active(&self, _context: &FilterContext) -> bool {
+        true
+    }
+
+    /// Whether friend passes the filter
+    fn matches(&self, friend: &SteamUser, context: &FilterContext) -> bool;
+
+    /// Shown in filter funnel, e.g. "account age >= 3"
+    fn name(&self) -> String;
+
+    /// Explains result of `matches` for friend, e.g. "account age 1y < 3y"
+    fn explain(&self, friend: &SteamUser, context: &FilterContext) -> String;
+
+    /// Whether filter uses data loaded only for friends who may pass it,
+    /// see `FilterContext::missing_on_demand_passes`
+    fn uses_loaded_on_demand(&self) -> bool {
+        false
+    }
+}
+
+impl<T: FriendFilter + ?Sized> FriendFilter for &T {
+    #[inline]
+    fn is_active(&self, context: &FilterContext) -> bool {
+        (**self).is_active(context)
+    }
+
+    #[inline]
+    fn matches(&self, friend: &SteamUser, context: &FilterContext) -> bool {
+        (**self).matches(friend, context)
+    }
+
+    #[inline]
+    fn name(&self) -> String {
+        (**self).name()
+    }
+
+    #[inline]
+    fn explain(&self, friend: &SteamUser, context: &FilterContext) -> String {
+        (**self).explain(friend, context)
+    }
+}
+
+/// Negates inner filter
+pub struct Not<F>(pub F);
+
+impl<F: FriendFilter> FriendFilter for Not<F> {
+    #[inline]
+    fn is_active(&self, context: &FilterContext) -> bool {
+        self.0.is_active(context)
+    }
+
+    #[inline]
+    fn matches(&self, friend: &SteamUser, context: &FilterContext) -> bool {
+        // missing data which passes fails under negation, so nobody would be loaded for
+        (context.missing_on_demand_passes && self.0.uses_loaded_on_demand())
+            || !self.0.matches(friend, context)
+    }
+
+    #[inline]
+    fn name(&self) -> String {
+        format!("NOT {}", self.0.name())
+    }
+
+    #[inline]
+    fn explain(&self, friend: &SteamUser, context: &FilterContext) -> String {
+        self.0.explain(friend, context)
+    }
+
+    #[inline]
+    fn uses_loaded_on_demand(&self) -> bool {
+        self.0.uses_loaded_on_demand()
+    }
+}
+
+/// Name of giveaway app for filter names and explanations
+#[inline]
+fn giveaway_app_name(context: &FilterContext) -> String {
+    context
+        .store_item_for_giveaway
+        .map(|item| format!("app {}", item.id))
+        .unwrap_or_else(|| "app for giveaway".to_owned())
+}
+
+#[derive(Clone, Serialize, Deserialize)]
 pub struct Filters {
     pub regions_and_countries: RegionsAndCountriesFilter,
     pub has_store_items: Vec<HasAppFilter>,
@@ -20,23 +175,304 @@ pub struct Filters {
     pub account_age: u32,
     pub exclude_unknown_age: bool,
 
+    /// friends for at least N days, 0 is off
+    #[serde(default)]
+    pub friends_for_days: u32,
+
+    #[serde(default)]
+    pub steam_level: SteamLevelFilter,
+
+    #[serde(default)]
+    pub exclude_community_banned: bool,
+    #[serde(default)]
+    pub exclude_trade_banned: bool,
+    /// exclude who got VAC ban in last N days, 0 is off
+    #[serde(default)]
+    pub vac_banned_within_days: u32,
+    /// exclude whose bans failed to load, ban filters do not consider them banned
+    #[serde(default)]
+    pub exclude_unknown_bans: bool,
+
+    /// online in last N days, 0 is off
+    #[serde(default)]
+    pub online_within_days: u32,
+    #[serde(default)]
+    pub presence: PresenceFilter,
+    #[serde(default)]
+    pub recent_activity: RecentActivityFilter,
+
     pub include_who_has_app_in_wishlist: bool,
+    #[serde(default)]
+    pub exclude_who_owns_app: bool,
     pub exclude_who_won_before: bool,
+    /// exclude who won in last N days, 0 is off
+    #[serde(default)]
+    pub won_within_days: u32,
+    /// exclude who won in last N giveaways, 0 is off
+    #[serde(default)]
+    pub won_within_giveaways: usize,
+    /// exclude who won at least N times, 0 is off
+    #[serde(default)]
+    pub max_wins: usize,
+
+    /// Combined with other filters by AND
+    #[serde(default)]
+    pub tree: FilterNode,
+}
+
+impl Default for Filters {
+    #[inline]
+    fn default() -> Self {
+        Self {
+            regions_and_countries: Default::default(),
+            has_store_items: Default::default(),
+            account_age: 0,
+            exclude_unknown_age: false,
+            friends_for_days: 0,
+            steam_level: Default::default(),
+            exclude_community_banned: false,
+            exclude_trade_banned: false,
+            vac_banned_within_days: 0,
+            exclude_unknown_bans: false,
+            online_within_days: 0,
+            presence: Default::default(),
+            recent_activity: Default::default(),
+            include_who_has_app_in_wishlist: false,
+            exclude_who_owns_app: true,
+            exclude_who_won_before: false,
+            won_within_days: 0,
+            won_within_giveaways: 0,
+            max_wins: 0,
+            tree: Default::default(),
+        }
+    }
 }
 
 impl Filters {
     pub fn reset(&mut self, friends: &Friends) {
         self.account_age = 0;
         self.exclude_unknown_age = false;
+        self.friends_for_days = 0;
+        self.steam_level = Default::default();
+        self.exclude_community_banned = false;
+        self.exclude_trade_banned = false;
+        self.vac_banned_within_days = 0;
+        self.exclude_unknown_bans = false;
+        self.online_within_days = 0;
+        self.presence = Default::default();
+        self.recent_activity = Default::default();
         self.has_store_items = Default::default();
         self.include_who_has_app_in_wishlist = false;
+        self.exclude_who_owns_app = true;
         self.exclude_who_won_before = false;
+        self.won_within_days = 0;
+        self.won_within_giveaways = 0;
+        self.max_wins = 0;
+        self.tree = Default::default();
         self.reset_regions_and_countries(friends);
     }
 
+    #[inline]
+    pub fn account_age_filter(&self) -> AccountAgeFilter {
+        AccountAgeFilter {
+            min_years: self.account_age,
+            exclude_unknown: self.exclude_unknown_age,
+        }
+    }
+
+    #[inline]
+    pub fn friends_since_filter(&self) -> FriendsSinceFilter {
+        FriendsSinceFilter {
+            min_days: self.friends_for_days,
+        }
+    }
+
+    /// All filters in order of applying, friend must pass every active one
+    pub fn chain(&self) -> Vec<Box<dyn FriendFilter + '_>> {
+        let mut chain: Vec<Box<dyn FriendFilter + '_>> = vec![
+            Box::new(self.account_age_filter()),
+            Box::new(self.friends_since_filter()),
+            Box::new(&self.steam_level),
+        ];
+        if self.exclude_unknown_bans {
+            chain.push(Box::new(KnownBans));
+        }
+        if self.exclude_community_banned {
+            chain.push(Box::new(Not(CommunityBanned)));
+        }
+        if self.exclude_trade_banned {
+            chain.push(Box::new(Not(TradeBanned)));
+        }
+        chain.push(Box::new(Not(VacBannedWithin(self.vac_banned_within_days))));
+        chain.push(Box::new(OnlineWithin(self.online_within_days)));
+        chain.push(Box::new(self.presence));
+        chain.push(Box::new(&self.recent_activity));
+        if self.exclude_who_won_before {
+            chain.push(Box::new(Not(WonBefore)));
+        }
+        chain.push(Box::new(Not(WonAtLeast(self.max_wins))));
+        chain.push(Box::new(Not(WonRecently)));
+        chain.push(Box::new(&self.regions_and_countries));
+        if self.include_who_has_app_in_wishlist {
+            chain.push(Box::new(GiveawayAppInWishlist));
+        }
+        if self.exclude_who_owns_app {
+            chain.push(Box::new(Not(GiveawayAppOwners)));
+        }
+        for filter in &self.has_store_items {
+            chain.push(Box::new(filter));
+        }
+        chain.push(Box::new(&self.tree));
+        chain
+    }
+
+    /// Whether filters use data which is loaded only for friends who may pass them,
+    /// e.g. achievements and recent playtime
+    pub fn use_loaded_on_demand(&self) -> bool {
+        self.uses_recent_playtime()
+            || self
+                .has_app_filters()
+                .iter()
+                .any(|filter| filter.uses_achievements())
+    }
+
+    /// Whether recent activity filter is on in panel or tree
+    pub fn uses_recent_playtime(&self) -> bool {
+        self.recent_activity.enabled || self.tree.uses_recent_playtime()
+    }
+
+    /// Has app filters from list and tree
+    pub fn has_app_filters(&self) -> Vec<&HasAppFilter> {
+        self.has_store_items
+            .iter()
+            .chain(self.tree.has_app_filters())
+            .collect()
+    }
+
+    /// Marks filters whose app has no user details as loading.
+    /// Returns ids of those apps.
+    pub fn start_loading_app_user_details(&mut self) -> HashSet<StoreItemId> {
+        let mut app_ids = HashSet::new();
+        let mut start_loading = |app: &Option<SteamStoreItem>, is_loading: &mut bool| {
+            if let Some(app) = app.as_ref().filter(|app| app.user_details.is_none()) {
+                *is_loading = true;
+                app_ids.insert(app.id);
+            }
+        };
+        for filter in &mut self.has_store_items {
+            start_loading(&filter.app, &mut filter.is_loading);
+        }
+        self.tree.for_each_mut(&mut |node| match node.kind {
+            FilterNodeKind::HasApp(ref mut filter) => {
+                start_loading(&filter.app, &mut filter.is_loading)
+            }
+            FilterNodeKind::InWishlist(ref mut filter) => {
+                start_loading(&filter.app, &mut filter.is_loading)
+            }
+            _ => {}
+        });
+        app_ids
+    }
+
+    /// Forgets user details of apps, e.g. they belong to the previous user
+    pub fn clear_app_user_details(&mut self) {
+        let clear = |app: &mut Option<SteamStoreItem>, is_loading: &mut bool| {
+            if let Some(app) = app.as_mut() {
+                app.user_details = None;
+            }
+            *is_loading = false;
+        };
+        for filter in &mut self.has_store_items {
+            clear(&mut filter.app, &mut filter.is_loading);
+        }
+        self.tree.for_each_mut(&mut |node| match node.kind {
+            FilterNodeKind::HasApp(ref mut filter) => {
+                clear(&mut filter.app, &mut filter.is_loading)
+            }
+            FilterNodeKind::InWishlist(ref mut filter) => {
+                clear(&mut filter.app, &mut filter.is_loading)
+            }
+            _ => {}
+        });
+    }
+
+    /// `None` if user details failed to load
+    pub fn set_app_user_details(
+        &mut self,
+        app_id: StoreItemId,
+        details: Option<&StoreItemUserDetails>,
+    ) {
+        for filter in self.has_store_items.iter_mut().filter(|filter| {
+            filter
+                .app
+                .as_ref()
+                .map(|app| app.id == app_id)
+                .unwrap_or_default()
+        }) {
+            if let Some(app) = filter.app.as_mut() {
+                app.user_details = details.cloned();
+            }
+            filter.is_loading = false;
+        }
+        self.tree.set_app_user_details(app_id, details);
+    }
+
     #[inline]
     pub fn reset_regions_and_countries(&mut self, friends: &Friends) {
         self.regions_and_countries = Default::default();
         self.regions_and_countries.available_countries = friends.regions.clone();
     }
+
+    /// Keeps included and excluded countries, offers the rest of friends regions
+    pub fn update_available_countries(&mut self, friends: &Friends) {
+        let regions = &mut self.regions_and_countries;
+        regions.available_countries = friends
+            .regions
+            .iter()
+            .filter(|region| {
+                !regions.include_countries.contains(region)
+                    && !regions.exclude_countries.contains(region)
+            })
+            .cloned()
+            .collect();
+    }
+}
+
+/// Result is sent with `Msg::UpdateUserDetailsOfHasAppFilter` tagged with generation of the token
+pub fn load_filter_app_user_details(
+    steam: Arc<RwLock<SteamApiClient>>,
+    sender: Sender<Msg>,
+    token: TaskToken,
+    app_id: StoreItemId,
+) {
+    thread::spawn(move || {
+        let steam = steam.read().unwrap().clone();
+        send_app_user_details(&steam, &sender, app_id, |app_user_details| {
+            Msg::UpdateUserDetailsOfHasAppFilter(token.generation, app_id, app_user_details)
+        });
+    });
+}
+
+/// Sends cached user details of app straight away and fetches them if they are outdated or missing.
+/// Failed refresh is sent as `Msg::Error`, so cached details are kept.
+pub fn send_app_user_details(
+    steam: &SteamApiClient,
+    sender: &Sender<Msg>,
+    app_id: StoreItemId,
+    to_msg: impl Fn(Result<StoreItemUserDetails, SteamError>) -> Msg,
+) {
+    let cached = steam.cached_app_user_details(app_id);
+    if let Some(ref cached) = cached {
+        let _ = sender.send(to_msg(Ok(cached.value.clone())));
+        if cached.is_fresh(CachedEndpoint::AppUserDetails) {
+            return;
+        }
+    }
+    let _ = sender.send(match (steam.app_user_details_of(app_id), cached) {
+        (Err(err), Some(_)) => Msg::Error(
+            format!("Failed to refresh user details of app {app_id}"),
+            err,
+        ),
+        (app_user_details, _) => to_msg(app_user_details),
+    });
 }
@@ -1,5 +1,9 @@
+use serde::{Deserialize, Serialize};
+
 use crate::steam::SteamUser;
 
+use super::{FilterContext, FriendFilter};
+
 pub const CIS_COUNTRIES: &[&str] = &[
     "AM", // Армения
     "AZ", // Азербайджан
@@ -45,7 +49,7 @@ pub const EU_COUNTRIES: &[&str] = &[
     "SE", // Швеция
 ];
 
-#[derive(Default)]
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum RegionFilter {
     #[default]
     Available,
@@ -53,83 +57,263 @@ pub enum RegionFilter {
     Exclude,
 }
 
-#[derive(Default)]
+/// User-defined set of countries, e.g. CIS, EU or LATAM
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RegionGroup {
+    pub name: String,
+    pub countries: Vec<String>,
+}
+
+impl RegionGroup {
+    #[inline]
+    pub fn new(name: &str, countries: &[&str]) -> Self {
+        Self {
+            name: name.to_owned(),
+            countries: countries
+                .iter()
+                .map(|&country| country.to_owned())
+                .collect(),
+        }
+    }
+
+    /// Built-in groups
+    pub fn defaults() -> Vec<Self> {
+        vec![
+            Self::new("CIS", CIS_COUNTRIES),
+            Self::new("EU", EU_COUNTRIES),
+        ]
+    }
+
+    /// Brings built-in groups back with their countries, other groups are kept
+    pub fn restore_defaults(groups: &mut Vec<Self>) {
+        for (index, default) in Self::defaults().into_iter().enumerate() {
+            match groups.iter_mut().find(|group| group.name == default.name) {
+                Some(group) => *group = default,
+                None => groups.insert(index.min(groups.len()), default),
+            }
+        }
+    }
+
+    #[inline]
+    pub fn contains(&self, country_code: &str) -> bool {
+        self.countries.iter().any(|country| country == country_code)
+    }
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct RegionGroupFilter {
+    /// Snapshot of group at the time it was included or excluded
+    pub group: RegionGroup,
+    pub filter: RegionFilter,
+}
+
+#[derive(Default, Clone, Serialize, Deserialize)]
+#[serde(from = "RegionsAndCountriesFilterData")]
 pub struct RegionsAndCountriesFilter {
     pub available_countries: Vec<String>,
     pub include_countries: Vec<String>,
     pub exclude_countries: Vec<String>,
 
     pub unknown: RegionFilter,
-    pub cis: RegionFilter,
-    pub eu: RegionFilter,
+    /// Included and excluded region groups, other groups are available
+    pub groups: Vec<RegionGroupFilter>,
 }
 
-pub fn apply_region_filters(friends: &mut Vec<SteamUser>, filters: &RegionsAndCountriesFilter) {
-    let include_unknown = matches!(filters.unknown, RegionFilter::Include);
-    let include_cis = matches!(filters.cis, RegionFilter::Include);
-    let include_eu = matches!(filters.eu, RegionFilter::Include);
-    let include_countries = !filters.include_countries.is_empty();
-
-    if include_countries || include_unknown || include_cis || include_eu {
-        friends.retain(|friend| {
-            friend.country_code.as_ref().map_or_else(
-                || include_unknown,
-                |friend_country| {
-                    if include_cis
-                        && CIS_COUNTRIES
-                            .iter()
-                            .any(|country| country == friend_country)
-                    {
-                        return true;
-                    }
-                    if include_eu && EU_COUNTRIES.iter().any(|country| country == friend_country) {
-                        return true;
-                    }
-                    if include_countries
-                        && filters
-                            .include_countries
-                            .iter()
-                            .any(|region_or_country| region_or_country == friend_country)
-                    {
-                        return true;
-                    }
-                    false
-                },
-            )
-        });
+/// Supports filters saved before region groups, with fixed `cis` and `eu` fields
+#[derive(Deserialize)]
+struct RegionsAndCountriesFilterData {
+    #[serde(default)]
+    available_countries: Vec<String>,
+    #[serde(default)]
+    include_countries: Vec<String>,
+    #[serde(default)]
+    exclude_countries: Vec<String>,
+    #[serde(default)]
+    unknown: RegionFilter,
+    #[serde(default)]
+    groups: Vec<RegionGroupFilter>,
+    #[serde(default)]
+    cis: RegionFilter,
+    #[serde(default)]
+    eu: RegionFilter,
+}
+
+impl From<RegionsAndCountriesFilterData> for RegionsAndCountriesFilter {
+    fn from(data: RegionsAndCountriesFilterData) -> Self {
+        let mut filter = Self {
+            available_countries: data.available_countries,
+            include_countries: data.include_countries,
+            exclude_countries: data.exclude_countries,
+            unknown: data.unknown,
+            groups: data.groups,
+        };
+        for (group, region_filter) in RegionGroup::defaults().into_iter().zip([data.cis, data.eu]) {
+            if !matches!(region_filter, RegionFilter::Available) {
+                filter.set_group_filter(&group, region_filter);
+            }
+        }
+        filter
     }
+}
 
-    let exclude_unknown = matches!(filters.unknown, RegionFilter::Exclude);
-    let exclude_cis = matches!(filters.cis, RegionFilter::Exclude);
-    let exclude_eu = matches!(filters.eu, RegionFilter::Exclude);
-    let exclude_countries = !filters.exclude_countries.is_empty();
-
-    if exclude_countries || exclude_unknown || exclude_cis || exclude_eu {
-        friends.retain(|friend| {
-            !friend.country_code.as_ref().map_or_else(
-                || exclude_unknown,
-                |friend_country| {
-                    if exclude_cis
-                        && CIS_COUNTRIES
-                            .iter()
-                            .any(|country| country == friend_country)
-                    {
-                        return true;
-                    }
-                    if exclude_eu && EU_COUNTRIES.iter().any(|country| country == friend_country) {
-                        return true;
-                    }
-                    if exclude_countries
-                        && filters
-                            .exclude_countries
-                            .iter()
-                            .any(|region_or_country| region_or_country == friend_country)
-                    {
-                        return true;
-                    }
-                    false
-                },
-            )
+impl RegionsAndCountriesFilter {
+    pub fn group_filter(&self, name: &str) -> RegionFilter {
+        self.groups
+            .iter()
+            .find(|group_filter| group_filter.group.name == name)
+            .map(|group_filter| group_filter.filter)
+            .unwrap_or_default()
+    }
+
+    pub fn set_group_filter(&mut self, group: &RegionGroup, filter: RegionFilter) {
+        self.groups
+            .retain(|group_filter| group_filter.group.name != group.name);
+        if !matches!(filter, RegionFilter::Available) {
+            self.groups.push(RegionGroupFilter {
+                group: group.clone(),
+                filter,
+            });
+        }
+    }
+
+    /// Updates snapshot of edited group, `None` if group was deleted
+    pub fn update_group(&mut self, old_name: &str, group: Option<&RegionGroup>) {
+        match group {
+            Some(group) => {
+                if let Some(group_filter) = self
+                    .groups
+                    .iter_mut()
+                    .find(|group_filter| group_filter.group.name == old_name)
+                {
+                    group_filter.group = group.clone();
+                }
+            }
+            None => self
+                .groups
+                .retain(|group_filter| group_filter.group.name != old_name),
+        }
+    }
+
+    /// Keeps only groups which are among `groups` and updates their snapshots
+    pub fn sync_groups(&mut self, groups: &[RegionGroup]) {
+        self.groups.retain_mut(|group_filter| {
+            match groups
+                .iter()
+                .find(|group| group.name == group_filter.group.name)
+            {
+                Some(group) => {
+                    group_filter.group = group.clone();
+                    true
+                }
+                None => false,
+            }
         });
     }
+
+    fn matches_any(&self, country_code: Option<&String>, filter: RegionFilter) -> bool {
+        let (unknown, countries) = match filter {
+            RegionFilter::Available => return false,
+            RegionFilter::Include => (
+                matches!(self.unknown, RegionFilter::Include),
+                &self.include_countries,
+            ),
+            RegionFilter::Exclude => (
+                matches!(self.unknown, RegionFilter::Exclude),
+                &self.exclude_countries,
+            ),
+        };
+        country_code.map_or(unknown, |friend_country| {
+            self.groups
+                .iter()
+                .filter(|group_filter| group_filter.filter == filter)
+                .any(|group_filter| group_filter.group.contains(friend_country))
+                || countries
+                    .iter()
+                    .any(|region_or_country| region_or_country == friend_country)
+        })
+    }
+
+    #[inline]
+    fn includes(&self, country_code: Option<&String>) -> bool {
+        self.matches_any(country_code, RegionFilter::Include)
+    }
+
+    #[inline]
+    fn excludes(&self, country_code: Option<&String>) -> bool {
+        self.matches_any(country_code, RegionFilter::Exclude)
+    }
+
+    #[inline]
+    fn has_includes(&self) -> bool {
+        !self.include_countries.is_empty()
+            || matches!(self.unknown, RegionFilter::Include)
+            || self
+                .groups
+                .iter()
+                .any(|group_filter| matches!(group_filter.filter, RegionFilter::Include))
+    }
+
+    #[inline]
+    fn has_excludes(&self) -> bool {
+        !self.exclude_countries.is_empty()
+            || matches!(self.unknown, RegionFilter::Exclude)
+            || self
+                .groups
+                .iter()
+                .any(|group_filter| matches!(group_filter.filter, RegionFilter::Exclude))
+    }
+}
+
+impl FriendFilter for RegionsAndCountriesFilter {
+    fn is_active(&self, _context: &FilterContext) -> bool {
+        self.has_includes() || self.has_excludes()
+    }
+
+    fn matches(&self, friend: &SteamUser, _context: &FilterContext) -> bool {
+        let country_code = friend.country_code.as_ref();
+        (!self.has_includes() || self.includes(country_code)) && !self.excludes(country_code)
+    }
+
+    fn name(&self) -> String {
+        "regions".to_owned()
+    }
+
+    fn explain(&self, friend: &SteamUser, _context: &FilterContext) -> String {
+        let country_code = friend.country_code.as_ref();
+        let country = country_code.map(String::as_str).unwrap_or("Unknown");
+        if self.has_includes() && !self.includes(country_code) {
+            format!("country {country} is not included")
+        } else if self.excludes(country_code) {
+            format!("country {country} is excluded")
+        } else {
+            format!("country {country} is allowed")
+        }
+    }
+}
+
+/// Who is from country, `None` is for unknown
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+pub struct CountryFilter(pub Option<String>);
+
+impl FriendFilter for CountryFilter {
+    fn matches(&self, friend: &SteamUser, _context: &FilterContext) -> bool {
+        friend.country_code == self.0
+    }
+
+    fn name(&self) -> String {
+        format!("country {}", self.0.as_deref().unwrap_or("Unknown"))
+    }
+
+    fn explain(&self, friend: &SteamUser, context: &FilterContext) -> String {
+        format!(
+            "country {} {} {}",
+            friend.country_code.as_deref().unwrap_or("Unknown"),
+            if self.matches(friend, context) {
+                "=="
+            } else {
+                "!="
+            },
+            self.0.as_deref().unwrap_or("Unknown")
+        )
+    }
 }
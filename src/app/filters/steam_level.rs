@@ -0,0 +1,50 @@
+use serde::{Deserialize, Serialize};
+
+use crate::steam::SteamUser;
+
+use super::{FilterContext, FriendFilter};
+
+/// Low levels are typical for alt accounts
+#[derive(Debug, Default, Clone, Copy, Serialize, Deserialize)]
+pub struct SteamLevelFilter {
+    pub min: u32,
+    /// Exclusive
+    pub max: Option<u32>,
+    pub exclude_unknown: bool,
+}
+
+impl FriendFilter for SteamLevelFilter {
+    fn is_active(&self, _context: &FilterContext) -> bool {
+        self.min > 0 || self.max.is_some() || self.exclude_unknown
+    }
+
+    fn matches(&self, friend: &SteamUser, _context: &FilterContext) -> bool {
+        friend
+            .steam_level
+            .map(|level| level >= self.min && self.max.is_none_or(|max| level < max))
+            .unwrap_or(!self.exclude_unknown)
+    }
+
+    fn name(&self) -> String {
+        let bounds = match self.max {
+            Some(max) => format!("level {}-{max}", self.min),
+            None => format!("level >= {}", self.min),
+        };
+        if self.exclude_unknown {
+            format!("{bounds} (known)")
+        } else {
+            bounds
+        }
+    }
+
+    fn explain(&self, friend: &SteamUser, _context: &FilterContext) -> String {
+        match friend.steam_level {
+            Some(level) if level < self.min => format!("level {level} < {}", self.min),
+            Some(level) => match self.max {
+                Some(max) if level >= max => format!("level {level} >= {max}"),
+                _ => format!("level {level} is within bounds"),
+            },
+            None => "level is unknown".to_owned(),
+        }
+    }
+}
@@ -0,0 +1,355 @@
+use std::fmt::Display;
+
+use serde::{Deserialize, Serialize};
+
+use crate::steam::{SteamUser, StoreItemId, StoreItemUserDetails};
+
+use super::{
+    AccountAgeFilter, CommunityBanned, CountryFilter, FilterContext, FriendFilter,
+    FriendsSinceFilter, HasAppFilter, OnlineWithin, PresenceFilter, RecentActivityFilter,
+    SteamLevelFilter, TradeBanned, VacBannedWithin, WishlistFilter, WonBefore,
+};
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum GroupOperator {
+    #[default]
+    And,
+    Or,
+}
+
+impl Display for GroupOperator {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Self::And => "AND",
+            Self::Or => "OR",
+        })
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum FilterNodeKind {
+    Group {
+        operator: GroupOperator,
+        children: Vec<FilterNode>,
+    },
+    Country(CountryFilter),
+    AccountAge(AccountAgeFilter),
+    FriendsSince(FriendsSinceFilter),
+    SteamLevel(SteamLevelFilter),
+    WonBefore,
+    HasApp(HasAppFilter),
+    InWishlist(WishlistFilter),
+    CommunityBanned,
+    TradeBanned,
+    VacBannedWithin(VacBannedWithin),
+    OnlineWithin(OnlineWithin),
+    Presence(PresenceFilter),
+    RecentActivity(RecentActivityFilter),
+}
+
+impl Default for FilterNodeKind {
+    #[inline]
+    fn default() -> Self {
+        Self::Group {
+            operator: Default::default(),
+            children: Default::default(),
+        }
+    }
+}
+
+/// Node of filter tree, where filters can be grouped with AND/OR and negated.
+/// Inactive nodes (e.g. empty groups or apps without loaded user details) are skipped.
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+pub struct FilterNode {
+    pub negate: bool,
+    pub kind: FilterNodeKind,
+}
+
+impl FilterNode {
+    #[inline]
+    pub fn new(kind: FilterNodeKind) -> Self {
+        Self {
+            negate: false,
+            kind,
+        }
+    }
+
+    pub fn for_each_mut(&mut self, f: &mut impl FnMut(&mut FilterNode)) {
+        f(self);
+        if let FilterNodeKind::Group {
+            ref mut children, ..
+        } = self.kind
+        {
+            for child in children {
+                child.for_each_mut(f);
+            }
+        }
+    }
+
+    pub fn has_app_filters(&self) -> Vec<&HasAppFilter> {
+        match self.kind {
+            FilterNodeKind::Group { ref children, .. } => children
+                .iter()
+                .flat_map(|child| child.has_app_filters())
+                .collect(),
+            FilterNodeKind::HasApp(ref filter) => vec![filter],
+            _ => vec![],
+        }
+    }
+
+    pub fn uses_recent_playtime(&self) -> bool {
+        match self.kind {
+            FilterNodeKind::Group { ref children, .. } => {
+                children.iter().any(|child| child.uses_recent_playtime())
+            }
+            FilterNodeKind::RecentActivity(ref filter) => filter.enabled,
+            _ => false,
+        }
+    }
+
+    pub fn set_app_user_details(
+        &mut self,
+        app_id: StoreItemId,
+        details: Option<&StoreItemUserDetails>,
+    ) {
+        self.for_each_mut(&mut |node| {
+            let (app, is_loading) = match node.kind {
+                FilterNodeKind::HasApp(ref mut filter) => (&mut filter.app, &mut filter.is_loading),
+                FilterNodeKind::InWishlist(ref mut filter) => {
+                    (&mut filter.app, &mut filter.is_loading)
+                }
+                _ => return,
+            };
+            if let Some(app) = app.as_mut().filter(|app| app.id == app_id) {
+                app.user_details = details.cloned();
+                *is_loading = false;
+            }
+        });
+    }
+
+    #[inline]
+    fn as_leaf_filter(&self) -> Option<&dyn FriendFilter> {
+        Some(match self.kind {
+            FilterNodeKind::Group { .. } => return None,
+            FilterNodeKind::Country(ref filter) => filter,
+            FilterNodeKind::AccountAge(ref filter) => filter,
+            FilterNodeKind::FriendsSince(ref filter) => filter,
+            FilterNodeKind::SteamLevel(ref filter) => filter,
+            FilterNodeKind::WonBefore => &WonBefore,
+            FilterNodeKind::HasApp(ref filter) => filter,
+            FilterNodeKind::InWishlist(ref filter) => filter,
+            FilterNodeKind::CommunityBanned => &CommunityBanned,
+            FilterNodeKind::TradeBanned => &TradeBanned,
+            FilterNodeKind::VacBannedWithin(ref filter) => filter,
+            FilterNodeKind::OnlineWithin(ref filter) => filter,
+            FilterNodeKind::Presence(ref filter) => filter,
+            FilterNodeKind::RecentActivity(ref filter) => filter,
+        })
+    }
+}
+
+impl FriendFilter for FilterNode {
+    fn is_active(&self, context: &FilterContext) -> bool {
+        match self.kind {
+            FilterNodeKind::Group { ref children, .. } => {
+                children.iter().any(|child| child.is_active(context))
+            }
+            _ => self
+                .as_leaf_filter()
+                .is_some_and(|filter| filter.is_active(context)),
+        }
+    }
+
+    fn matches(&self, friend: &SteamUser, context: &FilterContext) -> bool {
+        // missing data which passes fails under negation, so nobody would be loaded for
+        if self.negate && context.missing_on_demand_passes && self.uses_loaded_on_demand() {
+            return true;
+        }
+        let matches = match self.kind {
+            FilterNodeKind::Group {
+                operator,
+                ref children,
+            } => {
+                let mut active = children.iter().filter(|child| child.is_active(context));
+                match operator {
+                    GroupOperator::And => active.all(|child| child.matches(friend, context)),
+                    GroupOperator::Or => active.any(|child| child.matches(friend, context)),
+                }
+            }
+            _ => self
+                .as_leaf_filter()
+                .is_some_and(|filter| filter.matches(friend, context)),
+        };
+        matches != self.negate
+    }
+
+    fn name(&self) -> String {
+        self.to_string()
+    }
+
+    fn explain(&self, friend: &SteamUser, context: &FilterContext) -> String {
+        match self.kind {
+            FilterNodeKind::Group {
+                operator,
+                ref children,
+            } => {
+                let mut active = children.iter().filter(|child| child.is_active(context));
+                let explained = match operator {
+                    GroupOperator::And => active
+                        .find(|child| !child.matches(friend, context))
+                        .map(|child| child.explain(friend, context)),
+                    GroupOperator::Or => active
+                        .find(|child| child.matches(friend, context))
+                        .map(|child| child.explain(friend, context)),
+                };
+                explained.unwrap_or_else(|| {
+                    children
+                        .iter()
+                        .filter(|child| child.is_active(context))
+                        .map(|child| child.explain(friend, context))
+                        .collect::<Vec<_>>()
+                        .join("; ")
+                })
+            }
+            _ => self
+                .as_leaf_filter()
+                .map(|filter| filter.explain(friend, context))
+                .unwrap_or_default(),
+        }
+    }
+
+    fn uses_loaded_on_demand(&self) -> bool {
+        match self.kind {
+            FilterNodeKind::Group { ref children, .. } => {
+                children.iter().any(|child| child.uses_loaded_on_demand())
+            }
+            _ => self
+                .as_leaf_filter()
+                .is_some_and(|filter| filter.uses_loaded_on_demand()),
+        }
+    }
+}
+
+impl Display for FilterNode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.negate {
+            f.write_str("NOT ")?;
+        }
+        match self.kind {
+            FilterNodeKind::Group {
+                operator,
+                ref children,
+            } => {
+                f.write_str("(")?;
+                for (i, child) in children.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, " {operator} ")?;
+                    }
+                    write!(f, "{child}")?;
+                }
+                f.write_str(")")
+            }
+            _ => f.write_str(
+                &self
+                    .as_leaf_filter()
+                    .map(|filter| filter.name())
+                    .unwrap_or_default(),
+            ),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use serde_json::json;
+
+    use crate::app::{AchievementsOfFriends, Filters, HasAppMode, Winners};
+    use crate::steam::{FriendOwn, SteamId, SteamStoreItem};
+
+    use super::*;
+
+    const FRIEND_ID: SteamId = SteamId(76561198000000000);
+
+    fn friend() -> SteamUser {
+        serde_json::from_value(json!({
+            "steamid": FRIEND_ID.to_string(),
+            "personaname": "friend",
+            "avatarmedium": "",
+            "loccountrycode": null,
+        }))
+        .unwrap()
+    }
+
+    /// Filter of friends with at least half of achievements of the app they own
+    fn achievements_node(negate: bool) -> FilterNode {
+        let mut app: SteamStoreItem = serde_json::from_value(json!({
+            "type": "app",
+            "id": 10,
+            "name": "app",
+            "tiny_image": "",
+            "price": null,
+        }))
+        .unwrap();
+        let friend_own = FriendOwn {
+            id: FRIEND_ID,
+            playtime_twoweeks: 0,
+            playtime_total: 60,
+        };
+        app.user_details = Some(StoreItemUserDetails {
+            friends_own: HashMap::from([(FRIEND_ID, friend_own)]),
+            friends_want: HashMap::new(),
+        });
+        FilterNode {
+            negate,
+            kind: FilterNodeKind::HasApp(HasAppFilter {
+                app: Some(app),
+                mode: HasAppMode::Owns,
+                achievement_percent: 50,
+                ..Default::default()
+            }),
+        }
+    }
+
+    #[test]
+    fn missing_on_demand_data_keeps_load_candidates() {
+        let filters = Filters::default();
+        let winners = Winners::default();
+        let achievements = AchievementsOfFriends::new();
+        let recent_playtime = HashMap::new();
+        let mut context = FilterContext::new(
+            &filters,
+            &winners,
+            None,
+            &achievements,
+            &recent_playtime,
+            false,
+        );
+        context.missing_on_demand_passes = true;
+        let friend = friend();
+
+        let node = achievements_node(false);
+        assert!(node.is_active(&context));
+        assert!(node.matches(&friend, &context));
+
+        let negated = achievements_node(true);
+        assert!(negated.matches(&friend, &context));
+        let group = FilterNode::new(FilterNodeKind::Group {
+            operator: GroupOperator::And,
+            children: vec![negated],
+        });
+        assert!(group.matches(&friend, &context));
+
+        let mut negated_without_achievements = achievements_node(true);
+        if let FilterNodeKind::HasApp(ref mut filter) = negated_without_achievements.kind {
+            filter.achievement_percent = 0;
+        }
+        assert!(!negated_without_achievements.matches(&friend, &context));
+
+        // without achievements loaded the friend is excluded when filtering for real
+        context.missing_on_demand_passes = false;
+        assert!(!node.matches(&friend, &context));
+        assert!(achievements_node(true).matches(&friend, &context));
+    }
+}
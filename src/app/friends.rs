@@ -1,106 +1,692 @@
 use std::{
-    collections::HashSet,
-    sync::{Arc, RwLock, mpsc::Sender},
+    collections::{HashMap, HashSet},
+    sync::{
+        Arc, Mutex, RwLock,
+        atomic::{AtomicUsize, Ordering},
+        mpsc::Sender,
+    },
     thread,
 };
 
-use crate::steam::{RelationshipFilter, SteamApiClient, SteamUser, StoreItemUserDetails};
+use chrono::{DateTime, TimeDelta, Utc};
+
+use crate::steam::{
+    CachedEndpoint, Friend, PlayerAchievements, PlayerBans, RecentPlaytime, RelationshipFilter,
+    SteamApiClient, SteamError, SteamId, SteamStoreItem, SteamUser, StoreItemId,
+    StoreItemUserDetails,
+};
 
 use super::{
-    Msg, Winners, apply_account_age_filter, apply_has_app_filters, apply_region_filters,
-    exclude_who_won_before, filters::Filters, include_who_has_store_item_in_wishlist,
+    AchievementsOfFriends, FilterContext, Msg, Task, TaskToken, Winners, estimated_chances,
+    exact_chances, filters::Filters,
 };
 
+/// Chunks of friends loaded at the same time
+const PARALLEL_CHUNKS: usize = 4;
+
+/// Status of friends changes often, so older one is not shown and not filtered by
+const PRESENCE_MAX_AGE: TimeDelta = TimeDelta::minutes(15);
+
+pub struct FunnelStage {
+    pub filter: String,
+    /// Number of friends left after the filter
+    pub left: usize,
+}
+
+pub struct Exclusion {
+    pub filter: String,
+    pub reason: String,
+}
+
 #[derive(Default)]
 pub struct Friends {
     pub all: Vec<SteamUser>,
-    pub filtered: Vec<SteamUser>,
+    /// Indices of friends in `all` who passed the filters
+    pub filtered: Vec<usize>,
+    /// Chances of filtered friends to be among next winners,
+    /// empty if draw is not weighted or they are being estimated
+    pub chances: HashMap<SteamId, f64>,
+    /// Estimate of chances, restarted when filtered friends or the draw change
+    pub chances_task: Task,
+    /// Indices of friends in `all` who passed the filters if missing data loaded on demand passes,
+    /// that data is loaded only for them. Empty if filters use no such data.
+    pub load_candidates: Vec<usize>,
+    /// Revisions of filters, winners and friends and whether status of friends was fresh
+    /// at last filtering, friends are filtered again only when any of them changes
+    filtered_inputs: Option<(u64, u64, u64, bool)>,
+    /// Bumped on every change of friends or their loaded data
+    revision: u64,
     pub regions: Vec<String>,
 
+    /// Stages of last filtering, first one is all friends
+    pub funnel: Vec<FunnelStage>,
+    /// Filter which excluded friend in last filtering
+    pub exclusions: HashMap<SteamId, Exclusion>,
+
     pub is_loading: bool,
     pub loading_progress: f32,
+    /// Friends load, reloading or changing user drops results of the previous one
+    pub load_task: Task,
+    /// When friends were fetched from Steam, they may be shown from the disk cache
+    pub fetched_at: Option<DateTime<Utc>>,
+
+    /// Steam levels are loaded after friends
+    pub steam_levels_task: Task,
+    /// Number of friends whose levels were loaded
+    pub steam_levels_loaded: usize,
+
+    /// Cached achievements of friends who own apps from has app filters
+    pub achievements: AchievementsOfFriends,
+    pub achievements_loading: HashSet<StoreItemId>,
+    /// Friends whose achievements of app were requested, including failed ones
+    achievements_requested: HashMap<StoreItemId, HashSet<SteamId>>,
+    /// Achievements loads of current friends
+    pub achievements_task: Task,
+
+    /// Cached playtime in last 2 weeks of friends, `None` if failed to load.
+    /// Loaded only for load candidates of recent activity filter.
+    pub recent_playtime: HashMap<SteamId, Option<RecentPlaytime>>,
+    pub recent_playtime_loading: bool,
+    pub recent_playtime_task: Task,
 }
 
 impl Friends {
     pub fn update(&mut self, steam: Arc<RwLock<SteamApiClient>>, sender: Sender<Msg>) {
         self.is_loading = true;
+        self.loading_progress = 0.;
+        let token = self.load_task.restart();
+        thread::spawn(move || {
+            let generation = token.generation;
+            let _ = sender.send(match fetch_friends(steam, &sender, &token) {
+                Ok(friends_and_regions) => Msg::UpdateFriends(generation, friends_and_regions),
+                Err(err) => Msg::FriendsLoadFailed(generation, err),
+            });
+        });
+    }
+
+    /// Shows cached friends straight away and refreshes them in background if they are outdated
+    pub fn load(&mut self, steam: Arc<RwLock<SteamApiClient>>, sender: Sender<Msg>) {
+        self.is_loading = true;
+        self.loading_progress = 0.;
+        let token = self.load_task.restart();
+        thread::spawn(move || {
+            let generation = token.generation;
+            let cached = cached_friends(&steam.read().unwrap().clone());
+            if let Some((friends_and_regions, is_fresh)) = cached {
+                let _ = sender.send(Msg::UpdateFriends(generation, friends_and_regions));
+                if is_fresh {
+                    return;
+                }
+                let _ = sender.send(Msg::RefreshingFriends(generation));
+                let _ = sender.send(match fetch_friends(steam, &sender, &token) {
+                    Ok(friends_and_regions) => Msg::RefreshFriends(generation, friends_and_regions),
+                    Err(err) => Msg::FriendsLoadFailed(generation, err),
+                });
+            } else {
+                let _ = sender.send(match fetch_friends(steam, &sender, &token) {
+                    Ok(friends_and_regions) => Msg::UpdateFriends(generation, friends_and_regions),
+                    Err(err) => Msg::FriendsLoadFailed(generation, err),
+                });
+            }
+        });
+    }
+
+    /// Stops loading of friends and their levels, already shown ones are kept
+    pub fn cancel_loading(&mut self) {
+        self.load_task.cancel();
+        self.steam_levels_task.cancel();
+        self.is_loading = false;
+        self.loading_progress = 0.;
+    }
+
+    /// Loads Steam levels of all friends in background, cached levels are used if they are not outdated.
+    /// Levels are sent in chunks with `Msg::UpdateSteamLevels`.
+    pub fn load_steam_levels(&mut self, steam: &Arc<RwLock<SteamApiClient>>, sender: &Sender<Msg>) {
+        let token = self.steam_levels_task.restart();
+        let user_ids: Vec<_> = self.all.iter().map(|friend| friend.id).collect();
+        let steam = steam.read().unwrap().clone();
+        let sender = sender.clone();
+        thread::spawn(move || {
+            let failures = Failures::default();
+            process_chunks_in_parallel(&user_ids, 25, &token, |chunk| {
+                let levels = chunk
+                    .iter()
+                    .filter_map(|&user_id| {
+                        if let Some(cached) = steam
+                            .cached_steam_level(user_id)
+                            .filter(|cached| cached.is_fresh(CachedEndpoint::SteamLevel))
+                        {
+                            return Some((user_id, cached.value));
+                        }
+                        match steam.get_steam_level(user_id) {
+                            Ok(level) => Some((user_id, level)),
+                            Err(err) => {
+                                failures.add(err);
+                                None
+                            }
+                        }
+                    })
+                    .collect();
+                let _ = sender.send(Msg::UpdateSteamLevels(token.generation, levels));
+            });
+            failures.report(&sender, &token, |failed| {
+                format!("Failed to load Steam levels of {failed} friends")
+            });
+        });
+    }
+
+    pub fn set_steam_levels(&mut self, levels: Vec<(SteamId, Option<u32>)>) {
+        let levels: HashMap<_, _> = levels.into_iter().collect();
+        for friend in &mut self.all {
+            if let Some(&level) = levels.get(&friend.id) {
+                friend.steam_level = level;
+            }
+        }
+        self.steam_levels_loaded += levels.len();
+        self.revision += 1;
+    }
+
+    /// Forgets achievements and drops results of their running loads
+    pub fn reset_achievements(&mut self) {
+        self.achievements = Default::default();
+        self.achievements_loading = Default::default();
+        self.achievements_requested = Default::default();
+        self.achievements_task.restart();
+        self.revision += 1;
+    }
+
+    pub fn add_achievements(
+        &mut self,
+        app_id: StoreItemId,
+        achievements: Vec<(SteamId, PlayerAchievements)>,
+    ) {
+        self.achievements
+            .entry(app_id)
+            .or_default()
+            .extend(achievements);
+        self.revision += 1;
+    }
+
+    /// Loads achievements of owners for has app filters which use them.
+    /// Loaded only for load candidates whose achievements were not requested yet.
+    pub fn update_achievements(
+        &mut self,
+        filters: &Filters,
+        steam: &Arc<RwLock<SteamApiClient>>,
+        sender: &Sender<Msg>,
+    ) {
+        for filter in filters.has_app_filters() {
+            if !filter.uses_achievements() {
+                continue;
+            }
+            let Some(ref app) = filter.app else {
+                continue;
+            };
+            let Some(ref app_user_details) = app.user_details else {
+                continue;
+            };
+            if self.achievements_loading.contains(&app.id) {
+                continue;
+            }
+
+            let app_id = app.id;
+            let requested = self.achievements_requested.entry(app_id).or_default();
+            let owners = self
+                .load_candidates
+                .iter()
+                .map(|&index| self.all[index].id)
+                .filter(|id| {
+                    app_user_details.friends_own.contains_key(id) && !requested.contains(id)
+                })
+                .collect::<Vec<SteamId>>();
+            if owners.is_empty() {
+                continue;
+            }
+            requested.extend(&owners);
+            self.achievements_loading.insert(app_id);
+
+            // clone shares HTTP client, so the lock is not held during requests
+            let steam = steam.read().unwrap().clone();
+            let sender = sender.clone();
+            let token = self.achievements_task.token();
+            thread::spawn(move || {
+                let generation = token.generation;
+                let mut failed = 0;
+                let mut last_error = None;
+                for chunk in owners.chunks(10) {
+                    if token.is_cancelled() {
+                        return;
+                    }
+                    let mut achievements = vec![];
+                    for &user_id in chunk {
+                        match steam.get_player_achievements(user_id, app_id) {
+                            Ok(data) => achievements.push((user_id, data)),
+                            Err(err) => {
+                                failed += 1;
+                                last_error = Some(err);
+                            }
+                        }
+                    }
+                    let _ = sender.send(Msg::UpdatePlayerAchievements(
+                        generation,
+                        app_id,
+                        achievements,
+                    ));
+                }
+                if let Some(err) = last_error {
+                    let _ = sender.send(Msg::Error(
+                        format!("Failed to load achievements of {failed} friends for app {app_id}"),
+                        err,
+                    ));
+                }
+                let _ = sender.send(Msg::PlayerAchievementsLoaded(generation, app_id));
+            });
+        }
+    }
+
+    /// Loads playtime in last 2 weeks of load candidates who have none yet,
+    /// if recent activity filter is on and nothing is loading
+    pub fn update_recent_playtime(
+        &mut self,
+        filters: &Filters,
+        steam: &Arc<RwLock<SteamApiClient>>,
+        sender: &Sender<Msg>,
+    ) {
+        if !filters.uses_recent_playtime() || self.recent_playtime_loading {
+            return;
+        }
+        let user_ids: Vec<_> = self
+            .load_candidates
+            .iter()
+            .map(|&index| self.all[index].id)
+            .filter(|id| !self.recent_playtime.contains_key(id))
+            .collect();
+        if user_ids.is_empty() {
+            return;
+        }
+        self.recent_playtime_loading = true;
+
+        let token = self.recent_playtime_task.token();
+        let steam = steam.read().unwrap().clone();
+        let sender = sender.clone();
         thread::spawn(move || {
-            let sender = sender;
-            let friends_and_regions = fetch_friends(steam, &sender);
-            sender
-                .send(Msg::UpdateFriends(friends_and_regions))
-                .expect("Message should be sended");
+            let failures = Failures::default();
+            process_chunks_in_parallel(&user_ids, 25, &token, |chunk| {
+                let playtime = chunk
+                    .iter()
+                    .map(|&user_id| {
+                        if let Some(cached) = steam
+                            .cached_recent_playtime(user_id)
+                            .filter(|cached| cached.is_fresh(CachedEndpoint::RecentlyPlayedGames))
+                        {
+                            return (user_id, Some(cached.value));
+                        }
+                        match steam.get_recent_playtime(user_id) {
+                            Ok(playtime) => (user_id, Some(playtime)),
+                            Err(err) => {
+                                failures.add(err);
+                                (user_id, None)
+                            }
+                        }
+                    })
+                    .collect();
+                let _ = sender.send(Msg::UpdateRecentPlaytime(token.generation, playtime));
+            });
+            failures.report(&sender, &token, |failed| {
+                format!("Failed to load recently played games of {failed} friends")
+            });
+            let _ = sender.send(Msg::RecentPlaytimeLoaded(token.generation));
         });
     }
 
+    /// Forgets playtime of friends and drops results of its running load
+    pub fn reset_recent_playtime(&mut self) {
+        self.recent_playtime = Default::default();
+        self.recent_playtime_loading = false;
+        self.recent_playtime_task.restart();
+        self.revision += 1;
+    }
+
+    /// Forgets failed loads of playtime, so they are loaded again for load candidates
+    pub fn retry_failed_recent_playtime(&mut self) {
+        self.recent_playtime
+            .retain(|_, playtime| playtime.is_some());
+        self.revision += 1;
+    }
+
+    pub fn add_recent_playtime(&mut self, playtime: Vec<(SteamId, Option<RecentPlaytime>)>) {
+        self.recent_playtime.extend(playtime);
+        self.revision += 1;
+    }
+
+    /// Replaces friends, they are filtered again on the next update
+    pub fn set_all(
+        &mut self,
+        friends: Vec<SteamUser>,
+        regions: Vec<String>,
+        fetched_at: DateTime<Utc>,
+    ) {
+        self.all = friends;
+        self.regions = regions;
+        self.fetched_at = Some(fetched_at);
+        self.steam_levels_loaded = 0;
+        self.filtered.clear();
+        self.revision += 1;
+    }
+
+    /// Whether status of friends is recent enough to be shown and filtered by.
+    /// Friends from the disk cache may have status of the last day.
+    pub fn presence_is_fresh(&self) -> bool {
+        self.fetched_at
+            .is_some_and(|fetched_at| Utc::now() - fetched_at < PRESENCE_MAX_AGE)
+    }
+
+    #[inline]
+    pub fn filtered(&self) -> impl ExactSizeIterator<Item = &SteamUser> + Clone {
+        self.filtered.iter().map(|&index| &self.all[index])
+    }
+
+    /// Filters friends if any of filtering inputs changed since the last time.
+    /// `filters_revision` is bumped on every change of filters or store item for giveaway.
     pub fn update_filtered(
         &mut self,
         filters: &Filters,
+        filters_revision: u64,
         winners: &Winners,
-        app_user_details: Option<&StoreItemUserDetails>,
+        store_item_for_giveaway: Option<&SteamStoreItem>,
+        sender: &Sender<Msg>,
     ) {
         if self.all.is_empty() {
             return;
         }
+        let presence_is_fresh = self.presence_is_fresh();
+        let inputs = (
+            filters_revision,
+            winners.revision,
+            self.revision,
+            presence_is_fresh,
+        );
+        if self.filtered_inputs == Some(inputs) {
+            return;
+        }
+        self.filtered_inputs = Some(inputs);
+
+        let mut context = FilterContext::new(
+            filters,
+            winners,
+            store_item_for_giveaway,
+            &self.achievements,
+            &self.recent_playtime,
+            presence_is_fresh,
+        );
 
-        let mut filtered_friends = self.all.clone();
+        let mut left = (0..self.all.len()).collect::<Vec<_>>();
+        self.funnel = vec![FunnelStage {
+            filter: "all friends".to_owned(),
+            left: left.len(),
+        }];
+        self.exclusions.clear();
+        let chain = filters.chain();
+        for filter in &chain {
+            if !filter.is_active(&context) {
+                continue;
+            }
+            left.retain(|&index| {
+                let friend = &self.all[index];
+                let matches = filter.matches(friend, &context);
+                if !matches {
+                    self.exclusions.insert(
+                        friend.id,
+                        Exclusion {
+                            filter: filter.name(),
+                            reason: filter.explain(friend, &context),
+                        },
+                    );
+                }
+                matches
+            });
+            self.funnel.push(FunnelStage {
+                filter: filter.name(),
+                left: left.len(),
+            });
+        }
+
+        self.filtered = left;
+
+        self.load_candidates = if filters.use_loaded_on_demand() {
+            context.missing_on_demand_passes = true;
+            (0..self.all.len())
+                .filter(|&index| {
+                    chain.iter().all(|filter| {
+                        !filter.is_active(&context) || filter.matches(&self.all[index], &context)
+                    })
+                })
+                .collect()
+        } else {
+            vec![]
+        };
+        self.update_chances(
+            winners,
+            store_item_for_giveaway.and_then(|item| item.user_details.as_ref()),
+            sender,
+        );
+    }
 
-        apply_account_age_filter(&mut filtered_friends, filters);
-        if filters.exclude_who_won_before {
-            exclude_who_won_before(&mut filtered_friends, winners);
+    /// Computes chances of filtered friends straight away if they are exact,
+    /// otherwise estimates them in background, previous estimate is cancelled
+    fn update_chances(
+        &mut self,
+        winners: &Winners,
+        app_user_details: Option<&StoreItemUserDetails>,
+        sender: &Sender<Msg>,
+    ) {
+        self.chances.clear();
+        self.chances_task.cancel();
+        if !winners.weights.enabled {
+            return;
         }
-        apply_region_filters(&mut filtered_friends, &filters.regions_and_countries);
-        if filters.include_who_has_app_in_wishlist {
-            include_who_has_store_item_in_wishlist(&mut filtered_friends, app_user_details);
+        let weights =
+            winners
+                .weights
+                .weights_of(self.filtered(), &winners.all_time, app_user_details);
+        let winners_count = winners.next_number;
+        if let Some(chances) = exact_chances(&weights, winners_count) {
+            self.chances = chances;
+            return;
         }
-        apply_has_app_filters(&mut filtered_friends, &filters.has_store_items);
+        let token = self.chances_task.restart();
+        let sender = sender.clone();
+        thread::spawn(move || {
+            if let Some(chances) = estimated_chances(&weights, winners_count, &token) {
+                let _ = sender.send(Msg::UpdateChances(token.generation, chances));
+            }
+        });
+    }
+}
 
-        self.filtered = filtered_friends;
+/// Friends, their unique regions, number of friends whose summaries failed to load
+/// and when they were fetched
+pub struct FriendsAndTheirRegions(
+    pub Vec<SteamUser>,
+    pub Vec<String>,
+    pub usize,
+    pub DateTime<Utc>,
+);
+
+impl FriendsAndTheirRegions {
+    fn new(friends: Vec<SteamUser>, missing: usize, fetched_at: DateTime<Utc>) -> Self {
+        let mut regions: Vec<_> = friends
+            .iter()
+            .filter_map(|friend| friend.country_code.clone())
+            .collect::<HashSet<_>>()
+            .into_iter()
+            .collect();
+        regions.sort();
+        Self(friends, regions, missing, fetched_at)
     }
 }
 
-pub struct FriendsAndTheirRegions(pub Vec<SteamUser>, pub Vec<String>);
+/// Friends from the disk cache and whether they are not outdated
+pub fn cached_friends(steam: &SteamApiClient) -> Option<(FriendsAndTheirRegions, bool)> {
+    let friend_list = steam.cached_friend_list()?;
+    let user_ids = friend_list
+        .value
+        .iter()
+        .map(|friend| friend.id)
+        .collect::<Vec<_>>();
+    let summaries = steam.cached_user_summaries(&user_ids)?;
+    // friends are shown without bans if they are not cached, but are refreshed
+    let bans = steam.cached_player_bans(&user_ids);
+    let is_fresh = friend_list.is_fresh(CachedEndpoint::FriendList)
+        && summaries.is_fresh(CachedEndpoint::UserSummaries)
+        && bans
+            .as_ref()
+            .is_some_and(|bans| bans.is_fresh(CachedEndpoint::PlayerBans));
+    let mut friends = summaries.value;
+    set_friend_since(&mut friends, &friend_list.value);
+    if let Some(bans) = bans {
+        set_bans(&mut friends, bans.value);
+    }
+    Some((
+        FriendsAndTheirRegions::new(friends, 0, friend_list.fetched_at.min(summaries.fetched_at)),
+        is_fresh,
+    ))
+}
 
+/// Summaries have no friendship date, so it is taken from friend list
+fn set_friend_since(friends: &mut [SteamUser], friend_list: &[Friend]) {
+    let friend_since: HashMap<_, _> = friend_list
+        .iter()
+        .map(|friend| (friend.id, friend.friend_since))
+        .collect();
+    for friend in friends {
+        friend.friend_since = friend_since.get(&friend.id).copied().flatten();
+    }
+}
+
+fn set_bans(friends: &mut [SteamUser], mut bans: HashMap<SteamId, PlayerBans>) {
+    for friend in friends {
+        friend.bans = bans.remove(&friend.id);
+    }
+}
+
+/// Bans are loaded for the same chunks as summaries.
+/// Stops loading summaries of remaining friends when the token is cancelled
 pub fn fetch_friends(
     steam: Arc<RwLock<SteamApiClient>>,
     sender: &Sender<Msg>,
-) -> FriendsAndTheirRegions {
-    let mut unique_regions = HashSet::new();
-
-    let friends = {
-        let steam = steam.read().unwrap();
-        let mut list = vec![];
-
-        let friends = steam
-            .get_friend_list(RelationshipFilter::Friend, None)
-            .unwrap();
-        let chunks = friends.chunks(100);
-        let chunks_count = chunks.len();
-
-        for (n, chunk) in chunks.enumerate() {
-            if let Ok(data) =
-                steam.get_user_summaries(&chunk.iter().map(|f| f.id).collect::<Vec<_>>())
-            {
-                list.extend(data);
-            }
-            sender
-                .send(Msg::UpdateFriendsLoadProgress(
-                    (n + 1) as f32 / chunks_count as f32,
-                ))
-                .expect("Message should be sended");
-        }
+    token: &TaskToken,
+) -> Result<FriendsAndTheirRegions, SteamError> {
+    // clone shares HTTP client, so the lock is not held during requests
+    let steam = steam.read().unwrap().clone();
+    let friend_list = steam.get_friend_list(RelationshipFilter::Friend, None)?;
+    let chunks_count = friend_list.len().div_ceil(100);
 
-        list
-    };
+    let loaded_chunks = AtomicUsize::new(0);
+    let results = process_chunks_in_parallel(&friend_list, 100, token, |chunk| {
+        let user_ids = chunk.iter().map(|f| f.id).collect::<Vec<_>>();
+        let result = steam.get_user_summaries(&user_ids);
+        let bans = steam.get_player_bans(&user_ids);
+        let loaded = loaded_chunks.fetch_add(1, Ordering::Relaxed) + 1;
+        let _ = sender.send(Msg::UpdateFriendsLoadProgress(
+            token.generation,
+            loaded as f32 / chunks_count as f32,
+        ));
+        (chunk.len(), result, bans)
+    });
 
-    for friend in &friends {
-        if let Some(ref country_code) = friend.country_code {
-            unique_regions.insert(country_code.to_owned());
+    let mut friends = vec![];
+    for (chunk_len, result, bans) in results {
+        match result {
+            Ok(mut data) => {
+                match bans {
+                    Ok(bans) => set_bans(&mut data, bans),
+                    Err(err) if !token.is_cancelled() => {
+                        let _ = sender.send(Msg::Error(
+                            format!("Failed to load bans of {chunk_len} friends"),
+                            err,
+                        ));
+                    }
+                    Err(_) => {}
+                }
+                friends.extend(data);
+            }
+            Err(err) if !token.is_cancelled() => {
+                let _ = sender.send(Msg::Error(
+                    format!("Failed to load {chunk_len} friends"),
+                    err,
+                ));
+            }
+            Err(_) => {}
         }
     }
+    set_friend_since(&mut friends, &friend_list);
+    let missing = friend_list.len().saturating_sub(friends.len());
+
+    Ok(FriendsAndTheirRegions::new(friends, missing, Utc::now()))
+}
+
+/// Processes chunks of `chunk_size` items on up to `PARALLEL_CHUNKS` threads,
+/// each one takes the next chunk when it is done with the previous one.
+/// No more chunks are taken when the token is cancelled.
+/// Returns results of processed chunks in their order.
+fn process_chunks_in_parallel<T: Sync, R: Send>(
+    items: &[T],
+    chunk_size: usize,
+    token: &TaskToken,
+    process: impl Fn(&[T]) -> R + Sync,
+) -> Vec<R> {
+    let chunks: Vec<_> = items.chunks(chunk_size).collect();
+    let next_chunk = AtomicUsize::new(0);
+    let mut results: Vec<_> = thread::scope(|scope| {
+        let workers: Vec<_> = (0..PARALLEL_CHUNKS.min(chunks.len()))
+            .map(|_| {
+                scope.spawn(|| {
+                    let mut results = vec![];
+                    while !token.is_cancelled() {
+                        let n = next_chunk.fetch_add(1, Ordering::Relaxed);
+                        let Some(chunk) = chunks.get(n) else {
+                            break;
+                        };
+                        results.push((n, process(chunk)));
+                    }
+                    results
+                })
+            })
+            .collect();
+        workers
+            .into_iter()
+            .flat_map(|worker| worker.join().unwrap())
+            .collect()
+    });
+    results.sort_by_key(|(n, _)| *n);
+    results.into_iter().map(|(_, result)| result).collect()
+}
+
+/// Failed requests of parallel chunks, reported with one error when all chunks are done
+#[derive(Default)]
+struct Failures {
+    count: AtomicUsize,
+    last_error: Mutex<Option<SteamError>>,
+}
 
-    let mut regions: Vec<_> = unique_regions.into_iter().collect();
-    regions.sort();
+impl Failures {
+    fn add(&self, err: SteamError) {
+        self.count.fetch_add(1, Ordering::Relaxed);
+        *self.last_error.lock().unwrap() = Some(err);
+    }
 
-    FriendsAndTheirRegions(friends, regions)
+    /// Sends the last error with message made of number of failures, unless the load was cancelled
+    fn report(
+        self,
+        sender: &Sender<Msg>,
+        token: &TaskToken,
+        message: impl FnOnce(usize) -> String,
+    ) {
+        if let Some(err) = self.last_error.into_inner().unwrap() {
+            if !token.is_cancelled() {
+                let _ = sender.send(Msg::Error(message(self.count.into_inner()), err));
+            }
+        }
+    }
 }
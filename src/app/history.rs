@@ -0,0 +1,70 @@
+use chrono::{DateTime, Utc, serde::ts_seconds};
+use serde::{Deserialize, Serialize};
+
+use crate::steam::{Price, SteamId, SteamStoreItem, SteamUser, StoreItemId};
+
+use super::{Filters, RerollReason};
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct GiveawayItem {
+    pub id: StoreItemId,
+    pub name: String,
+    pub price: Option<Price>,
+}
+
+impl From<&SteamStoreItem> for GiveawayItem {
+    fn from(store_item: &SteamStoreItem) -> Self {
+        Self {
+            id: store_item.id,
+            name: store_item.name.clone(),
+            price: store_item.price.clone(),
+        }
+    }
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct GiveawayWinner {
+    pub id: SteamId,
+    /// Name at the time of the giveaway
+    pub name: String,
+}
+
+impl From<&SteamUser> for GiveawayWinner {
+    fn from(user: &SteamUser) -> Self {
+        Self {
+            id: user.id,
+            name: user.name.clone(),
+        }
+    }
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct ReplacedWinner {
+    pub winner: GiveawayWinner,
+    pub reason: RerollReason,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct GiveawayRecord {
+    #[serde(with = "ts_seconds")]
+    pub date: DateTime<Utc>,
+    pub store_item: Option<GiveawayItem>,
+    pub winners: Vec<GiveawayWinner>,
+    pub winners_requested: usize,
+    #[serde(default)]
+    pub replaced: Vec<ReplacedWinner>,
+    pub filters: Filters,
+}
+
+impl GiveawayRecord {
+    pub fn matches(&self, term: &str) -> bool {
+        let term = term.to_lowercase();
+        self.store_item
+            .as_ref()
+            .map(|item| item.name.to_lowercase().contains(&term))
+            .unwrap_or_default()
+            || self.winners.iter().any(|winner| {
+                winner.name.to_lowercase().contains(&term) || winner.id.to_string() == term
+            })
+    }
+}
@@ -1,22 +1,33 @@
+mod fair_draw;
 mod filters;
 mod friends;
+mod history;
 mod preferences;
+mod presets;
 mod storage_key;
+mod task;
+mod weights;
 mod winners;
 
 use egui_notify::Toasts;
+pub use fair_draw::*;
 pub use filters::*;
 pub use friends::*;
+pub use history::*;
 pub use preferences::*;
+pub use presets::*;
 use reqwest::{
     blocking::Client,
     header::{ACCEPT, USER_AGENT},
 };
+pub use task::*;
+pub use weights::*;
 pub use winners::*;
 
 use eframe::{CreationContext, Frame, egui::Context};
 use egui_extras::install_image_loaders;
 use std::{
+    collections::HashMap,
     sync::{
         Arc, RwLock,
         mpsc::{Receiver, Sender},
@@ -26,22 +37,42 @@ use std::{
 
 use crate::{
     steam::{
-        SteamApiClient, SteamStoreItem, SteamUser, StoreItemId, StoreItemUserDetails, TokenError,
+        PlayerAchievements, RecentPlaytime, SteamApiClient, SteamError, SteamId, SteamStoreItem,
+        SteamUser, StoreItemId, StoreItemUserDetails, TokenError,
     },
     ui::{
-        SearchSelect, main_window, settings_window, style_override, update_window, winners_window,
+        DiskImageLoader, SearchSelect, funnel_window, history_window, main_window, settings_window,
+        style_override, update_window, winners_window,
     },
 };
 
 pub enum Msg {
+    /// Context of failure and its error
+    Error(String, SteamError),
     AccessTokenSetted,
     NewVersionAvailable,
     UpdateFoundedFriends,
-    UpdateUserDetailsOfAppForGiveaway(StoreItemUserDetails),
-    UpdateUserDetailsOfHasAppFilter(StoreItemId, StoreItemUserDetails),
-    UpdateCurrentUser(SteamUser),
-    UpdateFriends(FriendsAndTheirRegions),
-    UpdateFriendsLoadProgress(f32),
+    // Messages of background tasks start with generation of the task,
+    // ones from stale generations are dropped
+    UpdateUserDetailsOfAppForGiveaway(u64, Result<StoreItemUserDetails, SteamError>),
+    UpdateUserDetailsOfHasAppFilter(u64, StoreItemId, Result<StoreItemUserDetails, SteamError>),
+    UpdateCurrentUser(u64, SteamUser),
+    UpdateFriends(u64, FriendsAndTheirRegions),
+    /// Cached friends are shown and fresh ones are being fetched
+    RefreshingFriends(u64),
+    /// Fresh friends replace cached ones, filters are kept
+    RefreshFriends(u64, FriendsAndTheirRegions),
+    FriendsLoadFailed(u64, SteamError),
+    UpdateFriendsLoadProgress(u64, f32),
+    /// Levels of friends, `None` if hidden
+    UpdateSteamLevels(u64, Vec<(SteamId, Option<u32>)>),
+    /// Playtime of friends in last 2 weeks, `None` if failed to load
+    UpdateRecentPlaytime(u64, Vec<(SteamId, Option<RecentPlaytime>)>),
+    RecentPlaytimeLoaded(u64),
+    UpdatePlayerAchievements(u64, StoreItemId, Vec<(SteamId, PlayerAchievements)>),
+    PlayerAchievementsLoaded(u64, StoreItemId),
+    /// Estimated chances of filtered friends to be among next winners
+    UpdateChances(u64, HashMap<SteamId, f64>),
 }
 
 pub struct App {
@@ -49,6 +80,8 @@ pub struct App {
 
     pub steam: Arc<RwLock<SteamApiClient>>,
     pub steam_access_token_buffer: String,
+    /// Profile load of user of access token, restarted when the token changes
+    pub current_user_task: Task,
 
     pub friends: Friends,
     pub friends_search_name: String,
@@ -56,12 +89,27 @@ pub struct App {
 
     pub store_item_for_giveaway: Option<SteamStoreItem>,
     pub app_for_giveaway_user_details_is_loading: bool,
+    pub app_for_giveaway_user_details_task: Task,
 
     pub search_select: SearchSelect,
 
     pub winners: Winners,
+    pub draw_proof_buffer: String,
+    pub history_search_term: String,
+    pub why_not_search_term: String,
 
     pub filters: Filters,
+    /// Bumped on every change of filters or store item for giveaway
+    pub filters_revision: u64,
+    /// User details loads of has app filters, restarted when user changes
+    pub filters_app_user_details_task: Task,
+    pub filter_presets: Vec<FilterPreset>,
+    pub filter_preset_name_buffer: String,
+    pub region_groups: Vec<RegionGroup>,
+    /// Index of region group open for editing, its name and country are typed in buffers
+    pub edited_region_group: Option<usize>,
+    pub region_group_name_buffer: String,
+    pub region_group_country_buffer: String,
 
     pub preferences: Preferences,
 
@@ -72,17 +120,22 @@ pub struct App {
 
     pub show_settings_window: bool,
     pub show_winners_window: bool,
+    pub show_history_window: bool,
+    pub show_funnel_window: bool,
     pub show_update_window: bool,
 }
 
 impl App {
     pub fn new(cc: &CreationContext) -> Self {
         install_image_loaders(&cc.egui_ctx);
+        DiskImageLoader::install(&cc.egui_ctx);
 
         let mut steam = SteamApiClient::new();
         let mut steam_access_token = String::new();
         let mut winners = Winners::default();
         let mut preferences = Preferences::default();
+        let mut filter_presets = vec![];
+        let mut region_groups = RegionGroup::defaults();
 
         let (sender, receiver) = std::sync::mpsc::channel();
 
@@ -95,16 +148,54 @@ impl App {
 
             if let Some(raw_str) = storage.get_string(storage_key::ALL_TIME_WINNERS) {
                 if let Ok(all_time_winners) = serde_json::from_str(&raw_str) {
-                    winners.all_time = all_time_winners;
+                    winners.legacy_all_time = all_time_winners;
                 }
             }
 
+            if let Some(raw_str) = storage.get_string(storage_key::GIVEAWAY_HISTORY) {
+                if let Ok(history) = serde_json::from_str(&raw_str) {
+                    winners.history = history;
+                }
+            }
+            winners.update_all_time();
+
             if let Some(raw_str) = storage.get_string(storage_key::AUTO_SAVE_ALL_TIME_WINNERS) {
                 if let Ok(auto_save) = serde_json::from_str(&raw_str) {
                     winners.auto_save_current = auto_save;
                 }
             }
 
+            if let Some(raw_str) = storage.get_string(storage_key::DRAW_WEIGHTS) {
+                if let Ok(weights) = serde_json::from_str(&raw_str) {
+                    winners.weights = weights;
+                }
+            }
+
+            if let Some(raw_str) = storage.get_string(storage_key::VERIFIABLE_DRAW) {
+                if let Ok(verifiable) = serde_json::from_str(&raw_str) {
+                    winners.verifiable = verifiable;
+                }
+            }
+
+            if let Some(raw_str) = storage.get_string(storage_key::DRAW_COMMITMENT) {
+                if let Ok(commitment) = serde_json::from_str(&raw_str) {
+                    winners.commitment = commitment;
+                }
+            }
+            winners.set_verifiable(winners.verifiable);
+
+            if let Some(raw_str) = storage.get_string(storage_key::FILTER_PRESETS) {
+                if let Ok(presets) = serde_json::from_str(&raw_str) {
+                    filter_presets = presets;
+                }
+            }
+
+            if let Some(raw_str) = storage.get_string(storage_key::REGION_GROUPS) {
+                if let Ok(groups) = serde_json::from_str(&raw_str) {
+                    region_groups = groups;
+                }
+            }
+
             if let Some(raw_str) = storage.get_string(storage_key::PREFERENCES) {
                 if let Ok(prefs) = serde_json::from_str(&raw_str) {
                     preferences = prefs;
@@ -125,6 +216,7 @@ impl App {
 
             steam: Arc::new(steam.into()),
             steam_access_token_buffer: steam_access_token,
+            current_user_task: Default::default(),
 
             friends: Default::default(),
             friends_search_name: Default::default(),
@@ -132,12 +224,24 @@ impl App {
 
             store_item_for_giveaway: Default::default(),
             app_for_giveaway_user_details_is_loading: false,
+            app_for_giveaway_user_details_task: Default::default(),
 
             search_select: SearchSelect::new(),
 
             winners,
+            draw_proof_buffer: Default::default(),
+            history_search_term: Default::default(),
+            why_not_search_term: Default::default(),
 
             filters: Default::default(),
+            filters_revision: 0,
+            filters_app_user_details_task: Default::default(),
+            filter_presets,
+            filter_preset_name_buffer: Default::default(),
+            region_groups,
+            edited_region_group: None,
+            region_group_name_buffer: Default::default(),
+            region_group_country_buffer: Default::default(),
 
             preferences,
 
@@ -147,10 +251,79 @@ impl App {
             main_current_page: 1,
             show_settings_window: false,
             show_winners_window: false,
+            show_history_window: false,
+            show_funnel_window: false,
 
             show_update_window: false,
         }
     }
+
+    pub fn load_filter_preset(&mut self, index: usize) {
+        let Some(preset) = self.filter_presets.get(index) else {
+            return;
+        };
+        let unknown_countries = preset.apply(&mut self.filters, &self.friends, &self.region_groups);
+        self.filters_revision += 1;
+        if unknown_countries.is_empty() {
+            self.toasts
+                .info(format!("Preset \"{}\" loaded!", preset.name));
+        } else {
+            self.toasts.warning(format!(
+                "Preset \"{}\" loaded, but it has countries that are not among friends regions: {}",
+                preset.name,
+                unknown_countries.join(", ")
+            ));
+        }
+        self.load_filters_app_user_details();
+    }
+
+    /// Loads user details of has app filters which have none
+    fn load_filters_app_user_details(&mut self) {
+        let token = self.filters_app_user_details_task.token();
+        for app_id in self.filters.start_loading_app_user_details() {
+            load_filter_app_user_details(
+                self.steam.clone(),
+                self.sender.clone(),
+                token.clone(),
+                app_id,
+            );
+        }
+    }
+
+    /// Result is sent with `Msg::UpdateUserDetailsOfAppForGiveaway`,
+    /// result of previous load is dropped
+    pub fn load_app_for_giveaway_user_details(&mut self) {
+        let Some(app_id) = self.store_item_for_giveaway.as_ref().map(|app| app.id) else {
+            return;
+        };
+        self.app_for_giveaway_user_details_is_loading = true;
+        let token = self.app_for_giveaway_user_details_task.restart();
+        let steam = self.steam.clone();
+        let sender = self.sender.clone();
+        thread::spawn(move || {
+            let steam = steam.read().unwrap().clone();
+            send_app_user_details(&steam, &sender, app_id, |app_user_details| {
+                Msg::UpdateUserDetailsOfAppForGiveaway(token.generation, app_user_details)
+            });
+        });
+    }
+
+    /// Saves current filters to preset with the name, overwrites existing one
+    pub fn save_filter_preset(&mut self, name: &str) {
+        let preset = FilterPreset {
+            name: name.to_owned(),
+            filters: self.filters.clone(),
+        };
+        match self
+            .filter_presets
+            .iter_mut()
+            .find(|preset| preset.name == name)
+        {
+            Some(existing) => *existing = preset,
+            None => self.filter_presets.push(preset),
+        }
+        self.toasts.success(format!("Preset \"{name}\" saved!"));
+    }
 }
 
 impl eframe::App for App {
@@ -161,12 +334,36 @@ impl eframe::App for App {
         );
         storage.set_string(
             storage_key::ALL_TIME_WINNERS,
-            serde_json::to_string(&self.winners.all_time).unwrap(),
+            serde_json::to_string(&self.winners.legacy_all_time).unwrap(),
+        );
+        storage.set_string(
+            storage_key::GIVEAWAY_HISTORY,
+            serde_json::to_string(&self.winners.history).unwrap(),
         );
         storage.set_string(
             storage_key::AUTO_SAVE_ALL_TIME_WINNERS,
             serde_json::to_string(&self.winners.auto_save_current).unwrap(),
         );
+        storage.set_string(
+            storage_key::DRAW_WEIGHTS,
+            serde_json::to_string(&self.winners.weights).unwrap(),
+        );
+        storage.set_string(
+            storage_key::VERIFIABLE_DRAW,
+            serde_json::to_string(&self.winners.verifiable).unwrap(),
+        );
+        storage.set_string(
+            storage_key::DRAW_COMMITMENT,
+            serde_json::to_string(&self.winners.commitment).unwrap(),
+        );
+        storage.set_string(
+            storage_key::FILTER_PRESETS,
+            serde_json::to_string(&self.filter_presets).unwrap(),
+        );
+        storage.set_string(
+            storage_key::REGION_GROUPS,
+            serde_json::to_string(&self.region_groups).unwrap(),
+        );
         storage.set_string(
             storage_key::PREFERENCES,
             serde_json::to_string(&self.preferences).unwrap(),
@@ -176,6 +373,9 @@ impl eframe::App for App {
     fn update(&mut self, ctx: &Context, _frame: &mut Frame) {
         if let Ok(msg) = self.receiver.try_recv() {
             match msg {
+                Msg::Error(context, err) => {
+                    self.toasts.error(format!("{context}: {err}"));
+                }
                 Msg::AccessTokenSetted => {
                     let steam = self.steam.read().unwrap();
                     if let Ok(ref token_info) = steam.access_token.info {
@@ -185,10 +385,25 @@ impl eframe::App for App {
                             .map(|current_user| current_user.id != token_info.user_id)
                             .unwrap_or(true)
                         {
-                            let steam = self.steam.clone();
+                            drop(steam);
+                            // results of loads for the previous user must not be shown
+                            self.friends.cancel_loading();
+                            self.friends.reset_achievements();
+                            self.friends.reset_recent_playtime();
+                            self.filters_app_user_details_task.restart();
+                            self.filters.clear_app_user_details();
+                            self.filters_revision += 1;
+                            self.load_filters_app_user_details();
+                            if let Some(ref mut app) = self.store_item_for_giveaway {
+                                app.user_details = None;
+                            }
+                            self.load_app_for_giveaway_user_details();
+
+                            let token = self.current_user_task.restart();
+                            let steam = self.steam.read().unwrap().clone();
                             let sender = self.sender.clone();
                             thread::spawn(move || {
-                                steam.write().unwrap().update_current_user(sender);
+                                steam.update_current_user(sender, token);
                             });
                         }
                     }
@@ -196,27 +411,56 @@ impl eframe::App for App {
                 Msg::NewVersionAvailable => {
                     self.show_update_window = true;
                 }
-                Msg::UpdateUserDetailsOfAppForGiveaway(app_user_details) => {
+                // results of stale or cancelled background tasks
+                Msg::UpdateUserDetailsOfAppForGiveaway(generation, _)
+                    if !self
+                        .app_for_giveaway_user_details_task
+                        .is_current(generation) => {}
+                Msg::UpdateUserDetailsOfHasAppFilter(generation, ..)
+                    if !self.filters_app_user_details_task.is_current(generation) => {}
+                Msg::UpdateCurrentUser(generation, _)
+                    if !self.current_user_task.is_current(generation) => {}
+                Msg::UpdateFriends(generation, _)
+                | Msg::RefreshingFriends(generation)
+                | Msg::RefreshFriends(generation, _)
+                | Msg::FriendsLoadFailed(generation, _)
+                | Msg::UpdateFriendsLoadProgress(generation, _)
+                    if !self.friends.load_task.is_current(generation) => {}
+                Msg::UpdateSteamLevels(generation, _)
+                    if !self.friends.steam_levels_task.is_current(generation) => {}
+                Msg::UpdatePlayerAchievements(generation, ..)
+                | Msg::PlayerAchievementsLoaded(generation, _)
+                    if !self.friends.achievements_task.is_current(generation) => {}
+                Msg::UpdateRecentPlaytime(generation, _)
+                | Msg::RecentPlaytimeLoaded(generation)
+                    if !self.friends.recent_playtime_task.is_current(generation) => {}
+                Msg::UpdateChances(generation, _)
+                    if !self.friends.chances_task.is_current(generation) => {}
+
+                Msg::UpdateUserDetailsOfAppForGiveaway(_, app_user_details) => {
                     self.app_for_giveaway_user_details_is_loading = false;
-                    if let Some(ref mut app) = self.store_item_for_giveaway {
-                        app.user_details = Some(app_user_details);
+                    match app_user_details {
+                        Ok(app_user_details) => {
+                            if let Some(ref mut app) = self.store_item_for_giveaway {
+                                app.user_details = Some(app_user_details);
+                                self.filters_revision += 1;
+                            }
+                        }
+                        Err(err) => {
+                            self.toasts
+                                .error(format!("Failed to load user details of app: {err}"));
+                        }
                     }
                 }
-                Msg::UpdateUserDetailsOfHasAppFilter(app_id, app_user_details) => {
-                    if let Some(has_app_filter) =
-                        self.filters.has_store_items.iter_mut().find(|filter| {
-                            filter
-                                .app
-                                .as_ref()
-                                .map(|app| app.id == app_id)
-                                .unwrap_or_default()
-                        })
-                    {
-                        if let Some(app) = has_app_filter.app.as_mut() {
-                            app.user_details = Some(app_user_details);
-                        }
-                        has_app_filter.is_loading = false;
+                Msg::UpdateUserDetailsOfHasAppFilter(_, app_id, app_user_details) => {
+                    if let Err(ref err) = app_user_details {
+                        self.toasts.error(format!(
+                            "Failed to load user details of app {app_id}: {err}"
+                        ));
                     }
+                    self.filters
+                        .set_app_user_details(app_id, app_user_details.as_ref().ok());
+                    self.filters_revision += 1;
                 }
                 Msg::UpdateFoundedFriends => {
                     if self.friends_search_name.is_empty() {
@@ -236,36 +480,103 @@ impl eframe::App for App {
                             .collect();
                     }
                 }
-                Msg::UpdateCurrentUser(user) => {
+                Msg::UpdateCurrentUser(_, user) => {
                     self.steam.write().unwrap().current_user = Some(user);
-                    self.friends.update(self.steam.clone(), self.sender.clone());
+                    self.friends.load(self.steam.clone(), self.sender.clone());
                 }
-                Msg::UpdateFriends(FriendsAndTheirRegions(friends, regions)) => {
+                Msg::FriendsLoadFailed(_, err) => {
                     self.friends.is_loading = false;
                     self.friends.loading_progress = 0.;
-                    self.friends.all = friends;
-                    self.friends.regions = regions.clone();
+                    self.toasts
+                        .error(format!("Failed to load friends list: {err}"));
+                }
+                Msg::UpdateFriends(
+                    _,
+                    FriendsAndTheirRegions(friends, regions, missing, fetched_at),
+                ) => {
+                    self.friends.is_loading = false;
+                    self.friends.loading_progress = 0.;
+                    self.friends.set_all(friends, regions, fetched_at);
+                    self.friends.reset_achievements();
+                    self.friends.reset_recent_playtime();
+                    self.friends.load_steam_levels(&self.steam, &self.sender);
 
                     self.friends_search_name = Default::default();
                     self.friends_search_results = Default::default();
 
                     self.filters.reset_regions_and_countries(&self.friends);
                     self.filters.has_store_items = Default::default();
+                    self.filters_revision += 1;
 
-                    self.toasts.success("Friends list loaded!");
+                    if missing > 0 {
+                        self.toasts.warning(format!(
+                            "Friends list loaded, but {missing} friends are missing!"
+                        ));
+                    } else {
+                        self.toasts.success("Friends list loaded!");
+                    }
+                }
+                Msg::RefreshingFriends(_) => {
+                    self.friends.is_loading = true;
+                    self.friends.loading_progress = 0.;
+                }
+                Msg::RefreshFriends(
+                    _,
+                    FriendsAndTheirRegions(friends, regions, missing, fetched_at),
+                ) => {
+                    self.friends.is_loading = false;
+                    self.friends.loading_progress = 0.;
+                    self.friends.set_all(friends, regions, fetched_at);
+                    self.friends.load_steam_levels(&self.steam, &self.sender);
+
+                    self.filters.update_available_countries(&self.friends);
+                    self.filters_revision += 1;
+                    let _ = self.sender.send(Msg::UpdateFoundedFriends);
+
+                    if missing > 0 {
+                        self.toasts.warning(format!(
+                            "Friends list refreshed, but {missing} friends are missing!"
+                        ));
+                    }
                 }
-                Msg::UpdateFriendsLoadProgress(progress) => {
+                Msg::UpdateFriendsLoadProgress(_, progress) => {
                     self.friends.loading_progress = progress;
                 }
+                Msg::UpdateSteamLevels(_, levels) => {
+                    self.friends.set_steam_levels(levels);
+                    let _ = self.sender.send(Msg::UpdateFoundedFriends);
+                }
+                Msg::UpdatePlayerAchievements(_, app_id, achievements) => {
+                    self.friends.add_achievements(app_id, achievements);
+                }
+                Msg::PlayerAchievementsLoaded(_, app_id) => {
+                    self.friends.achievements_loading.remove(&app_id);
+                }
+                Msg::UpdateRecentPlaytime(_, playtime) => {
+                    self.friends.add_recent_playtime(playtime);
+                }
+                Msg::RecentPlaytimeLoaded(_) => {
+                    self.friends.recent_playtime_loading = false;
+                }
+                Msg::UpdateChances(_, chances) => {
+                    self.friends.chances = chances;
+                }
             }
         }
 
+        self.friends
+            .update_achievements(&self.filters, &self.steam, &self.sender);
+        self.friends
+            .update_recent_playtime(&self.filters, &self.steam, &self.sender);
+
         if self.steam.read().unwrap().access_token.is_expired() {
             self.steam.write().unwrap().access_token.info = Err(TokenError::Expired);
         }
 
         main_window(self, ctx);
         winners_window(self, ctx);
+        history_window(self, ctx);
+        funnel_window(self, ctx);
         settings_window(self, ctx);
         update_window(self, ctx);
 
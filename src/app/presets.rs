@@ -0,0 +1,42 @@
+use serde::{Deserialize, Serialize};
+
+use super::{Filters, RegionGroup, friends::Friends};
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct FilterPreset {
+    pub name: String,
+    pub filters: Filters,
+}
+
+impl FilterPreset {
+    /// Replaces filters with preset ones.
+    /// Region groups of the preset are updated to current ones, deleted ones are dropped.
+    /// Countries which are not among friends regions are kept in include and exclude lists,
+    /// so the preset still means the same, e.g. nobody passes if only such countries are included.
+    /// Returns those countries.
+    pub fn apply(
+        &self,
+        filters: &mut Filters,
+        friends: &Friends,
+        region_groups: &[RegionGroup],
+    ) -> Vec<String> {
+        *filters = self.filters.clone();
+        filters.regions_and_countries.sync_groups(region_groups);
+
+        let regions = &filters.regions_and_countries;
+        let unknown = if friends.all.is_empty() {
+            vec![]
+        } else {
+            regions
+                .include_countries
+                .iter()
+                .chain(&regions.exclude_countries)
+                .filter(|country| !friends.regions.contains(country))
+                .cloned()
+                .collect()
+        };
+        filters.update_available_countries(friends);
+
+        unknown
+    }
+}
@@ -1,4 +1,10 @@
 pub const ACCESS_TOKEN: &str = "access_token";
 pub const ALL_TIME_WINNERS: &str = "all_time_winners_ids";
+pub const GIVEAWAY_HISTORY: &str = "giveaway_history";
 pub const AUTO_SAVE_ALL_TIME_WINNERS: &str = "auto_save_all_time_winners";
-pub const PREFERENCES: &str = "preferences";
\ No newline at end of file
+pub const PREFERENCES: &str = "preferences";
+pub const DRAW_WEIGHTS: &str = "draw_weights";
+pub const VERIFIABLE_DRAW: &str = "verifiable_draw";
+pub const DRAW_COMMITMENT: &str = "draw_commitment";
+pub const FILTER_PRESETS: &str = "filter_presets";
+pub const REGION_GROUPS: &str = "region_groups";
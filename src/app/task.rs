@@ -0,0 +1,57 @@
+use std::sync::{
+    Arc,
+    atomic::{AtomicBool, Ordering},
+};
+
+/// Background task which can be cancelled or restarted.
+/// Its messages carry generation, so results of previous runs are recognized and dropped.
+#[derive(Default)]
+pub struct Task {
+    generation: u64,
+    cancelled: Arc<AtomicBool>,
+}
+
+impl Task {
+    /// Cancels the current run and returns token of a new one
+    pub fn restart(&mut self) -> TaskToken {
+        self.cancel();
+        self.generation += 1;
+        self.cancelled = Default::default();
+        self.token()
+    }
+
+    /// Stops the current run, its results are dropped
+    #[inline]
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::Relaxed);
+    }
+
+    /// Token of the current run to start more work in it
+    #[inline]
+    pub fn token(&self) -> TaskToken {
+        TaskToken {
+            generation: self.generation,
+            cancelled: self.cancelled.clone(),
+        }
+    }
+
+    /// Whether message of the generation comes from the current run which was not cancelled
+    #[inline]
+    pub fn is_current(&self, generation: u64) -> bool {
+        generation == self.generation && !self.cancelled.load(Ordering::Relaxed)
+    }
+}
+
+/// Given to a background thread to tag its messages and to stop early
+#[derive(Debug, Clone)]
+pub struct TaskToken {
+    pub generation: u64,
+    cancelled: Arc<AtomicBool>,
+}
+
+impl TaskToken {
+    #[inline]
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::Relaxed)
+    }
+}
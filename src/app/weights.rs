@@ -0,0 +1,149 @@
+use std::collections::HashMap;
+
+use chrono::Utc;
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+use serde::{Deserialize, Serialize};
+
+use crate::steam::{SteamId, SteamUser, StoreItemUserDetails};
+
+use super::TaskToken;
+
+/// Draws simulated to estimate chances of several winners, error is about 1%
+const SIMULATED_DRAWS: usize = 10_000;
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct DrawWeights {
+    pub enabled: bool,
+    /// weight multiplier for who has store item for giveaway in wishlist
+    pub wishlist_multiplier: f32,
+    /// weight multiplier applied for each past win
+    pub past_win_multiplier: f32,
+    /// weight added for each year of account age
+    pub account_year_bonus: f32,
+}
+
+impl Default for DrawWeights {
+    #[inline]
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            wishlist_multiplier: 2.,
+            past_win_multiplier: 0.5,
+            account_year_bonus: 0.,
+        }
+    }
+}
+
+impl DrawWeights {
+    pub fn weight(
+        &self,
+        friend: &SteamUser,
+        all_time_winners: &HashMap<SteamId, usize>,
+        app_user_details: Option<&StoreItemUserDetails>,
+    ) -> f64 {
+        if !self.enabled {
+            return 1.;
+        }
+
+        let mut weight = 1.;
+        if let Some(years) = friend
+            .created_at
+            .and_then(|created_at| Utc::now().years_since(created_at))
+        {
+            weight += self.account_year_bonus as f64 * years as f64;
+        }
+        if app_user_details
+            .map(|details| details.is_wanted_by(friend.id))
+            .unwrap_or_default()
+        {
+            weight *= self.wishlist_multiplier as f64;
+        }
+        if let Some(wins) = all_time_winners.get(&friend.id) {
+            weight *= (self.past_win_multiplier as f64).powi(*wins as i32);
+        }
+        weight.max(0.)
+    }
+
+    /// Weights of friends in the draw, friends with zero weight are never drawn
+    pub fn weights_of<'a>(
+        &self,
+        friends: impl Iterator<Item = &'a SteamUser>,
+        all_time_winners: &HashMap<SteamId, usize>,
+        app_user_details: Option<&StoreItemUserDetails>,
+    ) -> Vec<(SteamId, f64)> {
+        friends
+            .map(|friend| {
+                (
+                    friend.id,
+                    self.weight(friend, all_time_winners, app_user_details),
+                )
+            })
+            .collect()
+    }
+}
+
+/// Chance of each friend to be among `winners_count` winners drawn one by one,
+/// `None` if it can't be computed exactly and has to be estimated
+pub fn exact_chances(
+    weights: &[(SteamId, f64)],
+    winners_count: usize,
+) -> Option<HashMap<SteamId, f64>> {
+    let drawable = weights.iter().filter(|(_, weight)| *weight > 0.).count();
+    if winners_count >= drawable {
+        return Some(
+            weights
+                .iter()
+                .map(|&(id, weight)| (id, if weight > 0. { 1. } else { 0. }))
+                .collect(),
+        );
+    }
+    if winners_count <= 1 {
+        let total: f64 = weights.iter().map(|(_, weight)| weight).sum();
+        return Some(
+            weights
+                .iter()
+                .map(|&(id, weight)| (id, weight / total))
+                .collect(),
+        );
+    }
+    None
+}
+
+/// Chance of each friend to be among several `winners_count` winners estimated
+/// by simulated draws with a fixed seed, `None` if the task was cancelled
+pub fn estimated_chances(
+    weights: &[(SteamId, f64)],
+    winners_count: usize,
+    token: &TaskToken,
+) -> Option<HashMap<SteamId, f64>> {
+    // picking friends with the smallest keys `-ln(u) / weight` is the same
+    // as picking them one by one with probability proportional to weight
+    let mut rng = ChaCha8Rng::seed_from_u64(0);
+    let mut wins = vec![0usize; weights.len()];
+    let mut keys = Vec::with_capacity(weights.len());
+    for draw in 0..SIMULATED_DRAWS {
+        if draw % 100 == 0 && token.is_cancelled() {
+            return None;
+        }
+        keys.clear();
+        keys.extend(
+            weights
+                .iter()
+                .enumerate()
+                .filter(|(_, (_, weight))| *weight > 0.)
+                .map(|(index, (_, weight))| (-(1. - rng.random::<f64>()).ln() / weight, index)),
+        );
+        keys.select_nth_unstable_by(winners_count - 1, |a, b| a.0.total_cmp(&b.0));
+        for &(_, index) in &keys[..winners_count] {
+            wins[index] += 1;
+        }
+    }
+    Some(
+        weights
+            .iter()
+            .zip(wins)
+            .map(|(&(id, _), wins)| (id, wins as f64 / SIMULATED_DRAWS as f64))
+            .collect(),
+    )
+}
@@ -1,32 +1,147 @@
 use std::collections::HashMap;
 
-use crate::steam::{SteamId, SteamUser};
+use std::fmt::Display;
 
-use super::friends::Friends;
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+
+use crate::steam::{SteamId, SteamStoreItem, SteamUser};
+
+use super::{
+    DrawCommitment, DrawProof, DrawWeights, Filters, GiveawayItem, GiveawayRecord, ReplacedWinner,
+    friends::Friends,
+};
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub enum RerollReason {
+    RegionLock,
+    AlreadyOwns,
+    NoAnswer,
+    Other,
+}
+
+impl RerollReason {
+    pub const ALL: [Self; 4] = [
+        Self::RegionLock,
+        Self::AlreadyOwns,
+        Self::NoAnswer,
+        Self::Other,
+    ];
+}
+
+impl Display for RerollReason {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Self::RegionLock => "Region lock",
+            Self::AlreadyOwns => "Already owns",
+            Self::NoAnswer => "No answer",
+            Self::Other => "Other",
+        })
+    }
+}
 
 #[derive(Default)]
 pub struct Winners {
     pub next_number: usize,
 
     pub current: Vec<SteamUser>,
-    pub all_time: HashMap<SteamId, usize>, // value is number of wins
+    /// Number of winners requested for current
+    pub current_requested: usize,
+    /// Who was replaced in current by re-roll
+    pub current_replaced: Vec<ReplacedWinner>,
+    /// Index of history record of current if it is saved
+    pub current_record: Option<usize>,
+    /// Store item for giveaway when current was drawn, saved with it
+    pub current_store_item: Option<GiveawayItem>,
+    /// Filters when current was drawn, saved with it
+    pub current_filters: Filters,
+
+    pub history: Vec<GiveawayRecord>,
+    /// Wins saved before history was introduced
+    pub legacy_all_time: HashMap<SteamId, usize>,
+    pub all_time: HashMap<SteamId, usize>, // value is number of wins, computed from history
+    /// Bumped on every change of history, wins, weights or number of next winners
+    pub revision: u64,
 
     pub saved: bool,
     pub auto_save_current: bool,
+
+    pub weights: DrawWeights,
+
+    pub verifiable: bool,
+    pub commitment: Option<DrawCommitment>,
+    pub proof: Option<DrawProof>,
 }
 
 impl Winners {
-    pub fn update_current(&mut self, friends: &Friends) {
-        use rand::seq::IteratorRandom;
-
-        let mut rng = rand::rng();
-        self.current = friends
-            .filtered
-            .iter()
-            .choose_multiple(&mut rng, self.next_number)
-            .into_iter()
-            .cloned()
-            .collect();
+    pub fn update_current(
+        &mut self,
+        friends: &Friends,
+        store_item: Option<&SteamStoreItem>,
+        filters: &Filters,
+    ) {
+        let app_user_details = store_item.and_then(|item| item.user_details.as_ref());
+        self.current_requested = self.next_number;
+        self.current_replaced.clear();
+        self.current_record = None;
+        self.current_store_item = store_item.map(Into::into);
+        self.current_filters = filters.clone();
+        if self.verifiable {
+            let candidates = friends
+                .filtered()
+                .map(|friend| friend.id)
+                .collect::<Vec<_>>();
+            let weights = self.weights.enabled.then(|| {
+                friends
+                    .filtered()
+                    .map(|friend| {
+                        (
+                            friend.id,
+                            self.weights
+                                .weight(friend, &self.all_time, app_user_details),
+                        )
+                    })
+                    .collect::<HashMap<_, _>>()
+            });
+            let proof = self
+                .commitment
+                .take()
+                .unwrap_or_else(DrawCommitment::new)
+                .draw(&candidates, weights.as_ref(), self.next_number);
+            self.current = proof
+                .winners
+                .iter()
+                .filter_map(|id| friends.filtered().find(|friend| friend.id == *id))
+                .cloned()
+                .collect();
+            self.proof = Some(proof);
+            self.commitment = Some(DrawCommitment::new());
+        } else if self.weights.enabled {
+            use rand::seq::IndexedRandom;
+
+            let mut rng = rand::rng();
+            self.current = friends
+                .filtered()
+                .collect::<Vec<_>>()
+                .choose_multiple_weighted(&mut rng, self.next_number, |friend| {
+                    self.weights
+                        .weight(friend, &self.all_time, app_user_details)
+                })
+                .map(|winners| winners.map(|&winner| winner.clone()).collect())
+                .unwrap_or_default();
+            self.proof = None;
+        } else {
+            use rand::seq::IteratorRandom;
+
+            let mut rng = rand::rng();
+            self.current = friends
+                .filtered()
+                .choose_multiple(&mut rng, self.next_number)
+                .into_iter()
+                .cloned()
+                .collect();
+            self.proof = None;
+        }
 
         if self.auto_save_current {
             self.save_current();
@@ -35,14 +150,142 @@ impl Winners {
         }
     }
 
+    /// Saves current winners to history with store item and filters they were drawn with
     pub fn save_current(&mut self) {
         self.saved = true;
-        for winner in &self.current {
-            if let Some(times) = self.all_time.get_mut(&winner.id) {
-                *times += 1;
+        if self.current.is_empty() {
+            return;
+        }
+        self.history.push(GiveawayRecord {
+            date: Utc::now(),
+            store_item: self.current_store_item.clone(),
+            winners: self.current.iter().map(Into::into).collect(),
+            winners_requested: self.current_requested,
+            replaced: self.current_replaced.clone(),
+            filters: self.current_filters.clone(),
+        });
+        self.current_record = Some(self.history.len() - 1);
+        self.update_all_time();
+    }
+
+    /// Replaces winner at `index` with new random pick from filtered friends
+    /// who are not current or replaced winners.
+    /// In verifiable mode the pick is the next one drawn with the proof seed.
+    /// Returns `false` if there is nobody to pick.
+    pub fn reroll(
+        &mut self,
+        index: usize,
+        reason: RerollReason,
+        friends: &Friends,
+        store_item: Option<&SteamStoreItem>,
+    ) -> bool {
+        if index >= self.current.len() {
+            return false;
+        }
+
+        // proof advances only if its pick is found among friends
+        let mut next_proof = None;
+        let new_winner = if let Some(ref proof) = self.proof {
+            let mut proof = proof.clone();
+            let new_winner = proof
+                .draw_next()
+                .and_then(|id| friends.all.iter().find(|friend| friend.id == id));
+            next_proof = Some(proof);
+            new_winner
+        } else {
+            use rand::seq::IndexedRandom;
+
+            let app_user_details = store_item.and_then(|item| item.user_details.as_ref());
+            let pool = friends
+                .filtered()
+                .filter(|friend| {
+                    !self.current.iter().any(|winner| winner.id == friend.id)
+                        && !self
+                            .current_replaced
+                            .iter()
+                            .any(|replaced| replaced.winner.id == friend.id)
+                })
+                .collect::<Vec<_>>();
+            let mut rng = rand::rng();
+            if self.weights.enabled {
+                pool.choose_weighted(&mut rng, |friend| {
+                    self.weights
+                        .weight(friend, &self.all_time, app_user_details)
+                })
+                .ok()
+                .copied()
             } else {
-                self.all_time.insert(winner.id, 1);
+                pool.choose(&mut rng).copied()
+            }
+        };
+
+        let Some(new_winner) = new_winner.cloned() else {
+            return false;
+        };
+        if next_proof.is_some() {
+            self.proof = next_proof;
+        }
+
+        let replaced = std::mem::replace(&mut self.current[index], new_winner);
+        if let Some(ref mut proof) = self.proof {
+            proof.replaced.push(replaced.id);
+        }
+        self.current_replaced.push(ReplacedWinner {
+            winner: (&replaced).into(),
+            reason,
+        });
+
+        if let Some(record) = self
+            .current_record
+            .and_then(|record_index| self.history.get_mut(record_index))
+        {
+            record.winners = self.current.iter().map(Into::into).collect();
+            record.replaced = self.current_replaced.clone();
+            self.update_all_time();
+        }
+        true
+    }
+
+    #[inline]
+    pub fn delete_record(&mut self, index: usize) {
+        self.history.remove(index);
+        match self.current_record {
+            Some(record_index) if record_index == index => {
+                self.current_record = None;
+                self.saved = false;
             }
+            Some(record_index) if record_index > index => {
+                self.current_record = Some(record_index - 1);
+            }
+            _ => {}
+        }
+        self.update_all_time();
+    }
+
+    #[inline]
+    pub fn clear_all_time(&mut self) {
+        self.history.clear();
+        self.legacy_all_time.clear();
+        self.current_record = None;
+        self.saved = false;
+        self.update_all_time();
+    }
+
+    pub fn update_all_time(&mut self) {
+        self.revision += 1;
+        self.all_time = self.legacy_all_time.clone();
+        for record in &self.history {
+            for winner in &record.winners {
+                *self.all_time.entry(winner.id).or_default() += 1;
+            }
+        }
+    }
+
+    #[inline]
+    pub fn set_verifiable(&mut self, verifiable: bool) {
+        self.verifiable = verifiable;
+        if verifiable && self.commitment.is_none() {
+            self.commitment = Some(DrawCommitment::new());
         }
     }
 }
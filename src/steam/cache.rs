@@ -0,0 +1,125 @@
+use std::{fs, path::PathBuf};
+
+use chrono::{DateTime, TimeDelta, Utc};
+use serde::{Deserialize, Serialize, de::DeserializeOwned};
+use sha2::{Digest, Sha256};
+
+/// Endpoints whose responses are kept in the disk cache
+#[derive(Debug, Clone, Copy)]
+pub enum CachedEndpoint {
+    FriendList,
+    UserSummaries,
+    AppUserDetails,
+    StoreSearch,
+    SteamLevel,
+    PlayerBans,
+    RecentlyPlayedGames,
+}
+
+impl CachedEndpoint {
+    /// Age after which cached data is refreshed
+    pub fn ttl(self) -> TimeDelta {
+        match self {
+            Self::FriendList => TimeDelta::hours(12),
+            Self::UserSummaries => TimeDelta::days(1),
+            Self::AppUserDetails => TimeDelta::hours(6),
+            Self::StoreSearch => TimeDelta::days(7),
+            Self::SteamLevel => TimeDelta::days(1),
+            Self::PlayerBans => TimeDelta::days(1),
+            Self::RecentlyPlayedGames => TimeDelta::hours(6),
+        }
+    }
+
+    fn dir_name(self) -> &'static str {
+        match self {
+            Self::FriendList => "friend_list",
+            Self::UserSummaries => "user_summaries",
+            Self::AppUserDetails => "app_user_details",
+            Self::StoreSearch => "store_search",
+            Self::SteamLevel => "steam_level",
+            Self::PlayerBans => "player_bans",
+            Self::RecentlyPlayedGames => "recently_played_games",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Cached<T> {
+    pub fetched_at: DateTime<Utc>,
+    pub value: T,
+}
+
+impl<T> Cached<T> {
+    #[inline]
+    pub fn is_fresh(&self, endpoint: CachedEndpoint) -> bool {
+        Utc::now() - self.fetched_at < endpoint.ttl()
+    }
+}
+
+/// Responses of Steam APIs saved on disk, does nothing without directory
+#[derive(Debug, Default)]
+pub struct DiskCache {
+    dir: Option<PathBuf>,
+}
+
+impl DiskCache {
+    #[inline]
+    pub fn new(dir: Option<PathBuf>) -> Self {
+        Self { dir }
+    }
+
+    /// `cache` directory next to the app storage
+    pub fn default_dir() -> Option<PathBuf> {
+        eframe::storage_dir(crate::APP_NAME).map(|dir| dir.join("cache"))
+    }
+
+    pub fn get<T: DeserializeOwned>(
+        &self,
+        endpoint: CachedEndpoint,
+        key: &str,
+    ) -> Option<Cached<T>> {
+        let bytes = fs::read(self.path(endpoint, key)?).ok()?;
+        serde_json::from_slice(&bytes).ok()
+    }
+
+    /// Cache is not essential, so failed writes are ignored
+    pub fn put<T: Serialize>(&self, endpoint: CachedEndpoint, key: &str, value: &T) {
+        let Some(path) = self.path(endpoint, key) else {
+            return;
+        };
+        let cached = Cached {
+            fetched_at: Utc::now(),
+            value,
+        };
+        if let (Some(dir), Ok(bytes)) = (path.parent(), serde_json::to_vec(&cached)) {
+            let _ = fs::create_dir_all(dir).and_then(|_| fs::write(&path, bytes));
+        }
+    }
+
+    pub fn clear(&self) {
+        if let Some(ref dir) = self.dir {
+            let _ = fs::remove_dir_all(dir);
+        }
+    }
+
+    fn path(&self, endpoint: CachedEndpoint, key: &str) -> Option<PathBuf> {
+        let dir = self.dir.as_ref()?.join(endpoint.dir_name());
+        Some(dir.join(format!("{}.json", file_key(key))))
+    }
+}
+
+/// Key itself if it can be a file name, otherwise its hash
+pub fn file_key(key: &str) -> String {
+    if !key.is_empty()
+        && key.len() <= 64
+        && key
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+    {
+        return key.to_owned();
+    }
+    Sha256::digest(key.as_bytes())[..16]
+        .iter()
+        .map(|b| format!("{b:02x}"))
+        .collect()
+}
@@ -1,13 +1,17 @@
 use std::collections::HashMap;
 use std::sync::mpsc::Sender;
 use std::sync::{Arc, LazyLock};
+use std::time::Duration;
 
-use crate::app::Msg;
+use crate::app::{Msg, TaskToken};
 
 use super::get_friend_list::{self, Friend, RelationshipFilter};
 use super::{
-    SteamId, SteamStoreItem, SteamUser, StoreItemId, StoreItemUserDetails,
-    get_current_user_country, get_user_summaries, store_item_user_details, store_search,
+    Cached, CachedEndpoint, DiskCache, PlayerAchievements, PlayerBans, RecentPlaytime,
+    SteamEndpoints, SteamError, SteamHttp, SteamId, SteamStoreItem, SteamUser, StoreItemId,
+    StoreItemUserDetails, TrafficMode, get_current_user_country, get_player_achievements,
+    get_player_bans, get_recently_played_games, get_steam_level, get_user_summaries,
+    store_item_user_details, store_search,
 };
 use base64::{Engine, prelude::BASE64_URL_SAFE_NO_PAD};
 use chrono::serde::ts_seconds;
@@ -16,15 +20,20 @@ use regex::Regex;
 use reqwest::{Url, blocking::Client, cookie::Jar};
 use serde::Deserialize;
 
+/// Cheap to clone, clones share HTTP client, cookies and retry state
+#[derive(Clone)]
 pub struct SteamApiClient {
-    http: Client,
+    http: Arc<SteamHttp>,
     cookies: Arc<Jar>,
+    endpoints: SteamEndpoints,
+    cache: Arc<DiskCache>,
 
     pub access_token: AccessToken,
 
     pub current_user: Option<SteamUser>,
 }
 
+#[derive(Clone)]
 pub struct AccessToken {
     pub info: Result<TokenInfo, TokenError>,
     pub token: String,
@@ -60,7 +69,7 @@ pub struct TokenInfo {
     pub expires_on: DateTime<Utc>,
 }
 
-#[derive(Debug, Default)]
+#[derive(Debug, Default, Clone, Copy)]
 pub enum TokenError {
     #[default]
     EmptyString,
@@ -69,14 +78,30 @@ pub enum TokenError {
 }
 
 impl SteamApiClient {
+    #[inline]
     pub fn new() -> Self {
+        Self::with_options(SteamEndpoints::from_env(), TrafficMode::from_env())
+    }
+
+    /// Disk cache is used only with Steam endpoints and live traffic
+    pub fn with_options(endpoints: SteamEndpoints, traffic: TrafficMode) -> Self {
         let cookie_store = Arc::new(Jar::default());
+        let cache = if endpoints.is_default() && traffic == TrafficMode::Live {
+            DiskCache::new(DiskCache::default_dir())
+        } else {
+            DiskCache::default()
+        };
         Self {
-            http: Client::builder()
-                .cookie_provider(cookie_store.clone())
-                .build()
-                .unwrap(),
+            http: Arc::new(SteamHttp::new(
+                Client::builder()
+                    .cookie_provider(cookie_store.clone())
+                    .build()
+                    .unwrap(),
+                traffic,
+            )),
             cookies: cookie_store,
+            endpoints,
+            cache: Arc::new(cache),
 
             access_token: Default::default(),
 
@@ -84,6 +109,36 @@ impl SteamApiClient {
         }
     }
 
+    #[inline]
+    pub fn endpoints(&self) -> &SteamEndpoints {
+        &self.endpoints
+    }
+
+    #[inline]
+    pub fn clear_cache(&self) {
+        self.cache.clear();
+    }
+
+    /// Id of the access token owner, data cached for them is not shown to other users
+    fn cache_user_id(&self) -> SteamId {
+        self.access_token
+            .info
+            .as_ref()
+            .map(|info| info.user_id)
+            .unwrap_or_default()
+    }
+
+    #[inline]
+    pub fn traffic(&self) -> &TrafficMode {
+        &self.http.traffic
+    }
+
+    /// Remaining time of waiting on a rate limit, if any request is waiting
+    #[inline]
+    pub fn rate_limit_wait(&self) -> Option<Duration> {
+        self.http.rate_limit.remaining()
+    }
+
     pub fn set_access_token(&mut self, token: &str) {
         let token = token.trim();
 
@@ -91,41 +146,56 @@ impl SteamApiClient {
         self.access_token.token = token.to_owned();
 
         if let Ok(ref token_info) = self.access_token.info {
-            let steam_urls = &[
-                Url::parse("https://steampowered.com").unwrap(),
-                Url::parse("https://api.steampowered.com").unwrap(),
-                Url::parse("https://store.steampowered.com").unwrap(),
+            let steam_urls = [
+                "https://steampowered.com",
+                &self.endpoints.api,
+                &self.endpoints.store,
             ];
 
-            for url in steam_urls {
+            for url in steam_urls
+                .into_iter()
+                .filter_map(|url| Url::parse(url).ok())
+            {
                 self.cookies.add_cookie_str(
                     &format!("steamLoginSecure={}%7C%7C{}", token_info.user_id, token),
-                    url,
+                    &url,
                 );
             }
         }
     }
 
-    pub fn update_current_user(&mut self, sender: Sender<Msg>) {
+    /// Result is sent with `Msg::UpdateCurrentUser` tagged with generation of the token.
+    /// Called on a clone, so the client is not locked during requests.
+    pub fn update_current_user(&self, sender: Sender<Msg>, token: TaskToken) {
         if let Ok(ref token_info) = self.access_token.info {
-            if let Ok(mut user) = self
-                .get_user_summaries(&[token_info.user_id])
-                .map(|users| users.into_iter().next().unwrap())
+            let user_id = token_info.user_id;
+            match self
+                .get_user_summaries(&[user_id])
+                .and_then(|users| users.into_iter().next().ok_or(SteamError::Private))
             {
-                user.country_code = Some(
-                    self.get_current_user_country()
-                        .expect("Current user country should be getted"),
-                );
-                let _ = sender.send(Msg::UpdateCurrentUser(user));
+                Ok(mut user) => {
+                    match self.get_current_user_country(user_id) {
+                        Ok(country_code) => user.country_code = Some(country_code),
+                        Err(err) => {
+                            let _ = sender
+                                .send(Msg::Error("Failed to load your country".to_owned(), err));
+                        }
+                    }
+                    let _ = sender.send(Msg::UpdateCurrentUser(token.generation, user));
+                }
+                Err(err) => {
+                    let _ = sender.send(Msg::Error("Failed to load your profile".to_owned(), err));
+                }
             }
         }
     }
 
-    fn get_current_user_country(&self) -> Result<String, reqwest::Error> {
+    fn get_current_user_country(&self, user_id: SteamId) -> Result<String, SteamError> {
         get_current_user_country::execute_request(
             &self.http,
+            &self.endpoints.api,
             &self.access_token.token,
-            self.access_token.info.as_ref().unwrap().user_id,
+            user_id,
         )
     }
 
@@ -133,36 +203,228 @@ impl SteamApiClient {
         &self,
         relationship: RelationshipFilter,
         user_id: Option<SteamId>,
-    ) -> Result<Vec<Friend>, reqwest::Error> {
+    ) -> Result<Vec<Friend>, SteamError> {
+        let cache_key = format!(
+            "{}-{relationship}",
+            user_id.unwrap_or_else(|| self.cache_user_id())
+        );
         get_friend_list::execute_request(
             &self.http,
+            &self.endpoints.api,
             &self.access_token.token,
             relationship,
             user_id,
         )
+        .inspect(|friends| {
+            self.cache
+                .put(CachedEndpoint::FriendList, &cache_key, friends)
+        })
     }
 
-    pub fn get_user_summaries(
+    /// Friends of the current user from the disk cache
+    pub fn cached_friend_list(&self) -> Option<Cached<Vec<Friend>>> {
+        self.cache.get(
+            CachedEndpoint::FriendList,
+            &format!("{}-{}", self.cache_user_id(), RelationshipFilter::Friend),
+        )
+    }
+
+    pub fn get_user_summaries(&self, user_ids: &[SteamId]) -> Result<Vec<SteamUser>, SteamError> {
+        get_user_summaries::execute_request(
+            &self.http,
+            &self.endpoints.api,
+            &self.access_token.token,
+            user_ids,
+        )
+        .inspect(|users| {
+            for user in users {
+                self.cache
+                    .put(CachedEndpoint::UserSummaries, &user.id.to_string(), user);
+            }
+        })
+    }
+
+    /// `None` if any of users is not cached, otherwise the oldest fetch time
+    pub fn cached_user_summaries(&self, user_ids: &[SteamId]) -> Option<Cached<Vec<SteamUser>>> {
+        let mut summaries = Cached {
+            fetched_at: Utc::now(),
+            value: Vec::with_capacity(user_ids.len()),
+        };
+        for user_id in user_ids {
+            let user: Cached<SteamUser> = self
+                .cache
+                .get(CachedEndpoint::UserSummaries, &user_id.to_string())?;
+            summaries.fetched_at = summaries.fetched_at.min(user.fetched_at);
+            summaries.value.push(user.value);
+        }
+        Some(summaries)
+    }
+
+    pub fn get_player_bans(
         &self,
         user_ids: &[SteamId],
-    ) -> Result<Vec<SteamUser>, reqwest::Error> {
-        get_user_summaries::execute_request(&self.http, &self.access_token.token, user_ids)
+    ) -> Result<HashMap<SteamId, PlayerBans>, SteamError> {
+        get_player_bans::execute_request(
+            &self.http,
+            &self.endpoints.api,
+            &self.access_token.token,
+            user_ids,
+        )
+        .inspect(|bans| {
+            for (user_id, bans) in bans {
+                self.cache
+                    .put(CachedEndpoint::PlayerBans, &user_id.to_string(), bans);
+            }
+        })
+    }
+
+    /// `None` if any of users is not cached, otherwise the oldest fetch time
+    pub fn cached_player_bans(
+        &self,
+        user_ids: &[SteamId],
+    ) -> Option<Cached<HashMap<SteamId, PlayerBans>>> {
+        let mut bans = Cached {
+            fetched_at: Utc::now(),
+            value: HashMap::with_capacity(user_ids.len()),
+        };
+        for &user_id in user_ids {
+            let user_bans: Cached<PlayerBans> = self
+                .cache
+                .get(CachedEndpoint::PlayerBans, &user_id.to_string())?;
+            bans.fetched_at = bans.fetched_at.min(user_bans.fetched_at);
+            bans.value.insert(user_id, user_bans.value);
+        }
+        Some(bans)
+    }
+
+    /// Total playtime of user in last 2 weeks
+    pub fn get_recent_playtime(&self, user_id: SteamId) -> Result<RecentPlaytime, SteamError> {
+        get_recently_played_games::execute_request(
+            &self.http,
+            &self.endpoints.api,
+            &self.access_token.token,
+            user_id,
+        )
+        .inspect(|playtime| {
+            self.cache.put(
+                CachedEndpoint::RecentlyPlayedGames,
+                &user_id.to_string(),
+                playtime,
+            )
+        })
+    }
+
+    pub fn cached_recent_playtime(&self, user_id: SteamId) -> Option<Cached<RecentPlaytime>> {
+        self.cache
+            .get(CachedEndpoint::RecentlyPlayedGames, &user_id.to_string())
+    }
+
+    pub fn get_player_achievements(
+        &self,
+        user_id: SteamId,
+        app_id: StoreItemId,
+    ) -> Result<PlayerAchievements, SteamError> {
+        get_player_achievements::execute_request(
+            &self.http,
+            &self.endpoints.api,
+            &self.access_token.token,
+            user_id,
+            app_id,
+        )
+    }
+
+    /// `None` if level is hidden by privacy settings
+    pub fn get_steam_level(&self, user_id: SteamId) -> Result<Option<u32>, SteamError> {
+        get_steam_level::execute_request(
+            &self.http,
+            &self.endpoints.api,
+            &self.access_token.token,
+            user_id,
+        )
+        .inspect(|level| {
+            self.cache
+                .put(CachedEndpoint::SteamLevel, &user_id.to_string(), level)
+        })
+    }
+
+    pub fn cached_steam_level(&self, user_id: SteamId) -> Option<Cached<Option<u32>>> {
+        self.cache
+            .get(CachedEndpoint::SteamLevel, &user_id.to_string())
     }
 
     pub fn app_user_details(
         &self,
         app_ids: &[StoreItemId],
-    ) -> Result<HashMap<StoreItemId, Option<StoreItemUserDetails>>, reqwest::Error> {
-        store_item_user_details::execute_request(&self.http, app_ids)
+    ) -> Result<HashMap<StoreItemId, Option<StoreItemUserDetails>>, SteamError> {
+        store_item_user_details::execute_request(&self.http, &self.endpoints.store, app_ids)
+            .inspect(|apps| {
+                for (app_id, details) in apps {
+                    if let Some(details) = details {
+                        self.cache.put(
+                            CachedEndpoint::AppUserDetails,
+                            &format!("{}-{app_id}", self.cache_user_id()),
+                            details,
+                        );
+                    }
+                }
+            })
+    }
+
+    pub fn cached_app_user_details(
+        &self,
+        app_id: StoreItemId,
+    ) -> Option<Cached<StoreItemUserDetails>> {
+        self.cache.get(
+            CachedEndpoint::AppUserDetails,
+            &format!("{}-{app_id}", self.cache_user_id()),
+        )
+    }
+
+    /// User details of single app, `SteamError::Private` if they are unavailable
+    pub fn app_user_details_of(
+        &self,
+        app_id: StoreItemId,
+    ) -> Result<StoreItemUserDetails, SteamError> {
+        self.app_user_details(&[app_id])?
+            .remove(&app_id)
+            .flatten()
+            .ok_or(SteamError::Private)
     }
 
     pub fn store_search(
         &self,
         term: &str,
         country_code: Option<&str>,
-    ) -> Result<Vec<SteamStoreItem>, reqwest::Error> {
-        store_search::execute_request(&self.http, term, country_code)
+    ) -> Result<Vec<SteamStoreItem>, SteamError> {
+        store_search::execute_request(&self.http, &self.endpoints.store, term, country_code)
+            .inspect(|items| {
+                self.cache.put(
+                    CachedEndpoint::StoreSearch,
+                    &store_search_cache_key(term, country_code),
+                    items,
+                )
+            })
     }
+
+    pub fn cached_store_search(
+        &self,
+        term: &str,
+        country_code: Option<&str>,
+    ) -> Option<Cached<Vec<SteamStoreItem>>> {
+        self.cache.get(
+            CachedEndpoint::StoreSearch,
+            &store_search_cache_key(term, country_code),
+        )
+    }
+}
+
+#[inline]
+fn store_search_cache_key(term: &str, country_code: Option<&str>) -> String {
+    format!(
+        "{}-{}",
+        country_code.unwrap_or("us"),
+        term.trim().to_lowercase()
+    )
 }
 
 fn parse_access_token(token: &str) -> Result<TokenInfo, TokenError> {
@@ -0,0 +1,43 @@
+use std::env;
+
+/// Base URLs of Steam APIs, can be pointed to a local stand-in
+/// with `STEAM_API_URL` and `STEAM_STORE_URL` environment variables
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SteamEndpoints {
+    /// Web API, `api.steampowered.com`
+    pub api: String,
+    /// Store API, `store.steampowered.com`
+    pub store: String,
+}
+
+impl Default for SteamEndpoints {
+    #[inline]
+    fn default() -> Self {
+        Self {
+            api: "https://api.steampowered.com".to_owned(),
+            store: "https://store.steampowered.com".to_owned(),
+        }
+    }
+}
+
+impl SteamEndpoints {
+    pub fn from_env() -> Self {
+        let default = Self::default();
+        let from_var = |name: &str, default: String| {
+            env::var(name)
+                .ok()
+                .map(|url| url.trim().trim_end_matches('/').to_owned())
+                .filter(|url| !url.is_empty())
+                .unwrap_or(default)
+        };
+        Self {
+            api: from_var("STEAM_API_URL", default.api),
+            store: from_var("STEAM_STORE_URL", default.store),
+        }
+    }
+
+    #[inline]
+    pub fn is_default(&self) -> bool {
+        *self == Self::default()
+    }
+}
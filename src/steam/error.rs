@@ -0,0 +1,95 @@
+use std::fmt::Display;
+
+use reqwest::{StatusCode, blocking::Response, header::RETRY_AFTER};
+use serde::de::DeserializeOwned;
+
+#[derive(Debug)]
+pub enum SteamError {
+    /// Request could not be sent or response could not be read
+    Request(reqwest::Error),
+    /// 401 or 403, access token is invalid or expired
+    Unauthorized(StatusCode),
+    /// 429, `retry_after` is in seconds
+    RateLimited {
+        retry_after: Option<u64>,
+    },
+    /// Any other unsuccessful status
+    Status(StatusCode),
+    Decode(serde_json::Error),
+    /// Data is hidden with privacy settings
+    Private,
+    /// Recording of traffic could not be written or read
+    Io(std::io::Error),
+    /// Replayed request has no recorded response, URL is without access token
+    NotRecorded(String),
+}
+
+impl Display for SteamError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Request(err) => write!(f, "request failed ({err})"),
+            Self::Unauthorized(status) => {
+                write!(f, "access denied ({status}), check access token")
+            }
+            Self::RateLimited {
+                retry_after: Some(secs),
+            } => write!(f, "rate limited, retry after {secs} s."),
+            Self::RateLimited { retry_after: None } => f.write_str("rate limited"),
+            Self::Status(status) => write!(f, "unexpected response ({status})"),
+            Self::Decode(err) => write!(f, "unexpected response data ({err})"),
+            Self::Private => f.write_str("data is private"),
+            Self::Io(err) => write!(f, "traffic recording failed ({err})"),
+            Self::NotRecorded(url) => write!(f, "no recorded response for {url}"),
+        }
+    }
+}
+
+impl std::error::Error for SteamError {}
+
+impl From<reqwest::Error> for SteamError {
+    #[inline]
+    fn from(err: reqwest::Error) -> Self {
+        Self::Request(err)
+    }
+}
+
+impl From<std::io::Error> for SteamError {
+    #[inline]
+    fn from(err: std::io::Error) -> Self {
+        Self::Io(err)
+    }
+}
+
+impl From<serde_json::Error> for SteamError {
+    #[inline]
+    fn from(err: serde_json::Error) -> Self {
+        Self::Decode(err)
+    }
+}
+
+impl SteamError {
+    /// `None` if status is successful
+    pub fn from_response(response: &Response) -> Option<Self> {
+        let status = response.status();
+        match status {
+            _ if status.is_success() => None,
+            StatusCode::UNAUTHORIZED | StatusCode::FORBIDDEN => Some(Self::Unauthorized(status)),
+            StatusCode::TOO_MANY_REQUESTS => Some(Self::RateLimited {
+                retry_after: response
+                    .headers()
+                    .get(RETRY_AFTER)
+                    .and_then(|value| value.to_str().ok())
+                    .and_then(|value| value.trim().parse().ok()),
+            }),
+            _ => Some(Self::Status(status)),
+        }
+    }
+}
+
+/// Checks status and decodes JSON body
+pub fn parse_response<T: DeserializeOwned>(response: Response) -> Result<T, SteamError> {
+    if let Some(err) = SteamError::from_response(&response) {
+        return Err(err);
+    }
+    Ok(serde_json::from_slice(&response.bytes()?)?)
+}
@@ -1,7 +1,6 @@
-use reqwest::blocking::Client;
 use serde::Deserialize;
 
-use super::SteamId;
+use super::{SteamError, SteamHttp, SteamId, parse_response};
 
 #[derive(Debug, Deserialize)]
 struct GetUserCountry {
@@ -14,17 +13,17 @@ struct Response {
 }
 
 pub fn execute_request(
-    http: &Client,
+    http: &SteamHttp,
+    base_url: &str,
     access_token: &str,
     user_id: SteamId,
-) -> Result<String, reqwest::Error> {
-    const URL: &str = "https://api.steampowered.com/IUserAccountService/GetUserCountry/v1";
-    http.post(URL)
-        .form(&[
-            ("access_token", access_token),
-            ("steamid", &user_id.to_string()),
-        ])
-        .send()?
-        .json::<GetUserCountry>()
+) -> Result<String, SteamError> {
+    const PATH: &str = "/IUserAccountService/GetUserCountry/v1";
+    let request = http.post(format!("{base_url}{PATH}")).form(&[
+        ("access_token", access_token),
+        ("steamid", &user_id.to_string()),
+    ]);
+    http.send(request)
+        .and_then(parse_response::<GetUserCountry>)
         .map(|res| res.response.country)
 }
@@ -1,8 +1,8 @@
 use std::fmt::Display;
 
-use super::SteamId;
-use reqwest::blocking::Client;
-use serde::Deserialize;
+use super::{SteamError, SteamHttp, SteamId, parse_response};
+use chrono::{DateTime, Utc, serde::ts_seconds_option};
+use serde::{Deserialize, Serialize};
 
 #[derive(Deserialize)]
 struct GetFriendList {
@@ -10,14 +10,16 @@ struct GetFriendList {
 }
 
 #[allow(dead_code)]
-#[derive(Deserialize)]
+#[derive(Serialize, Deserialize)]
 pub struct Friend {
     #[serde(rename = "steamid")]
     pub id: SteamId,
     pub relationship: Relationship,
+    #[serde(default, with = "ts_seconds_option")]
+    pub friend_since: Option<DateTime<Utc>>,
 }
 
-#[derive(Deserialize)]
+#[derive(Serialize, Deserialize)]
 pub enum Relationship {
     #[serde(alias = "friend")]
     Friend,
@@ -43,22 +45,27 @@ impl Display for RelationshipFilter {
 }
 
 pub fn execute_request(
-    http: &Client,
+    http: &SteamHttp,
+    base_url: &str,
     access_token: &str,
     relationship: RelationshipFilter,
     user_id: Option<SteamId>,
-) -> Result<Vec<Friend>, reqwest::Error> {
-    const URL: &str = "https://api.steampowered.com/ISteamUserOAuth/GetFriendList/v1";
-    http.get(URL)
-        .query(&[
-            ("access_token", access_token),
-            ("relationship", &relationship.to_string()),
-            (
-                "steamid",
-                &user_id.map(|id| id.to_string()).unwrap_or_default(),
-            ),
-        ])
-        .send()?
-        .json::<GetFriendList>()
+) -> Result<Vec<Friend>, SteamError> {
+    const PATH: &str = "/ISteamUserOAuth/GetFriendList/v1";
+    let request = http.get(format!("{base_url}{PATH}")).query(&[
+        ("access_token", access_token),
+        ("relationship", &relationship.to_string()),
+        (
+            "steamid",
+            &user_id.map(|id| id.to_string()).unwrap_or_default(),
+        ),
+    ]);
+    http.send(request)
+        .and_then(parse_response::<GetFriendList>)
         .map(|res| res.friends)
+        .map_err(|err| match (user_id, err) {
+            // friends list of other user is hidden
+            (Some(_), SteamError::Unauthorized(_)) => SteamError::Private,
+            (_, err) => err,
+        })
 }
@@ -0,0 +1,71 @@
+use serde::Deserialize;
+
+use super::{SteamError, SteamHttp, SteamId, StoreItemId};
+
+#[derive(Debug, Deserialize)]
+struct GetPlayerAchievements {
+    playerstats: PlayerStats,
+}
+
+#[derive(Debug, Deserialize)]
+struct PlayerStats {
+    #[serde(default)]
+    achievements: Vec<Achievement>,
+    #[serde(default)]
+    error: Option<String>,
+    success: bool,
+}
+
+#[derive(Debug, Deserialize)]
+struct Achievement {
+    achieved: u8,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub enum PlayerAchievements {
+    /// Percent of achieved achievements
+    Completion(f32),
+    /// Due to privacy settings
+    Private,
+    /// App has no achievements or user has no stats for it
+    NoStats,
+}
+
+pub fn execute_request(
+    http: &SteamHttp,
+    base_url: &str,
+    access_token: &str,
+    user_id: SteamId,
+    app_id: StoreItemId,
+) -> Result<PlayerAchievements, SteamError> {
+    const PATH: &str = "/ISteamUserStats/GetPlayerAchievements/v1";
+    let request = http.get(format!("{base_url}{PATH}")).query(&[
+        ("access_token", access_token),
+        ("steamid", &user_id.to_string()),
+        ("appid", &app_id.to_string()),
+    ]);
+    let response = http.send(request)?;
+    let error = SteamError::from_response(&response);
+    // private stats and apps without stats are reported with unsuccessful status and JSON body
+    serde_json::from_slice::<GetPlayerAchievements>(&response.bytes()?)
+        .map_err(|err| error.unwrap_or(SteamError::Decode(err)))
+        .map(|res| {
+            let stats = res.playerstats;
+            if !stats.success {
+                return if stats
+                    .error
+                    .map(|err| err.to_lowercase().contains("not public"))
+                    .unwrap_or_default()
+                {
+                    PlayerAchievements::Private
+                } else {
+                    PlayerAchievements::NoStats
+                };
+            }
+            if stats.achievements.is_empty() {
+                return PlayerAchievements::NoStats;
+            }
+            let achieved = stats.achievements.iter().filter(|a| a.achieved > 0).count();
+            PlayerAchievements::Completion(achieved as f32 * 100. / stats.achievements.len() as f32)
+        })
+}
@@ -0,0 +1,71 @@
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+
+use super::{SteamError, SteamHttp, SteamId, parse_response};
+
+#[derive(Debug, Deserialize)]
+struct GetPlayerBans {
+    players: Vec<PlayerBansOf>,
+}
+
+#[derive(Debug, Deserialize)]
+struct PlayerBansOf {
+    #[serde(rename = "SteamId")]
+    id: SteamId,
+    #[serde(flatten)]
+    bans: PlayerBans,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PlayerBans {
+    #[serde(rename = "CommunityBanned")]
+    pub community_banned: bool,
+    #[serde(rename = "VACBanned")]
+    pub vac_banned: bool,
+    #[serde(rename = "NumberOfVACBans")]
+    pub vac_bans: u32,
+    #[serde(rename = "NumberOfGameBans")]
+    pub game_bans: u32,
+    /// Of any type, 0 if there are no bans
+    #[serde(rename = "DaysSinceLastBan")]
+    pub days_since_last_ban: u32,
+    /// `none`, `probation` or `banned`
+    #[serde(rename = "EconomyBan")]
+    pub economy_ban: String,
+}
+
+impl PlayerBans {
+    #[inline]
+    pub fn is_trade_banned(&self) -> bool {
+        self.economy_ban != "none"
+    }
+}
+
+pub fn execute_request(
+    http: &SteamHttp,
+    base_url: &str,
+    access_token: &str,
+    user_ids: &[SteamId],
+) -> Result<HashMap<SteamId, PlayerBans>, SteamError> {
+    const PATH: &str = "/ISteamUser/GetPlayerBans/v1";
+    let request = http.get(format!("{base_url}{PATH}")).query(&[
+        ("access_token", access_token),
+        (
+            "steamids",
+            &user_ids
+                .iter()
+                .map(ToString::to_string)
+                .collect::<Vec<String>>()
+                .join(","),
+        ),
+    ]);
+    http.send(request)
+        .and_then(parse_response::<GetPlayerBans>)
+        .map(|res| {
+            res.players
+                .into_iter()
+                .map(|player| (player.id, player.bans))
+                .collect()
+        })
+}
@@ -0,0 +1,57 @@
+use serde::{Deserialize, Serialize};
+
+use super::{SteamError, SteamHttp, SteamId, parse_response};
+
+#[derive(Debug, Deserialize)]
+struct GetRecentlyPlayedGames {
+    response: RecentlyPlayedGames,
+}
+
+#[derive(Debug, Deserialize)]
+struct RecentlyPlayedGames {
+    /// Missing if games are private
+    total_count: Option<u32>,
+    #[serde(default)]
+    games: Vec<RecentlyPlayedGame>,
+}
+
+#[derive(Debug, Deserialize)]
+struct RecentlyPlayedGame {
+    #[serde(default)]
+    playtime_2weeks: u32,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub enum RecentPlaytime {
+    /// Minutes played in all games in last 2 weeks
+    Minutes(u32),
+    /// Due to privacy settings
+    Private,
+}
+
+pub fn execute_request(
+    http: &SteamHttp,
+    base_url: &str,
+    access_token: &str,
+    user_id: SteamId,
+) -> Result<RecentPlaytime, SteamError> {
+    const PATH: &str = "/IPlayerService/GetRecentlyPlayedGames/v1";
+    let request = http.get(format!("{base_url}{PATH}")).query(&[
+        ("access_token", access_token),
+        ("steamid", &user_id.to_string()),
+        // all games
+        ("count", "0"),
+    ]);
+    http.send(request)
+        .and_then(parse_response::<GetRecentlyPlayedGames>)
+        .map(|res| match res.response.total_count {
+            Some(_) => RecentPlaytime::Minutes(
+                res.response
+                    .games
+                    .iter()
+                    .map(|game| game.playtime_2weeks)
+                    .sum(),
+            ),
+            None => RecentPlaytime::Private,
+        })
+}
@@ -0,0 +1,31 @@
+use serde::Deserialize;
+
+use super::{SteamError, SteamHttp, SteamId, parse_response};
+
+#[derive(Debug, Deserialize)]
+struct GetSteamLevel {
+    response: SteamLevel,
+}
+
+#[derive(Debug, Deserialize)]
+struct SteamLevel {
+    /// Missing if profile is private
+    player_level: Option<u32>,
+}
+
+/// `None` if level is hidden by privacy settings
+pub fn execute_request(
+    http: &SteamHttp,
+    base_url: &str,
+    access_token: &str,
+    user_id: SteamId,
+) -> Result<Option<u32>, SteamError> {
+    const PATH: &str = "/IPlayerService/GetSteamLevel/v1";
+    let request = http.get(format!("{base_url}{PATH}")).query(&[
+        ("access_token", access_token),
+        ("steamid", &user_id.to_string()),
+    ]);
+    http.send(request)
+        .and_then(parse_response::<GetSteamLevel>)
+        .map(|res| res.response.player_level)
+}
@@ -1,5 +1,4 @@
-use super::{SteamId, SteamUser};
-use reqwest::blocking::Client;
+use super::{SteamError, SteamHttp, SteamId, SteamUser, parse_response};
 use serde::Deserialize;
 
 #[derive(Debug, Deserialize)]
@@ -8,24 +7,24 @@ struct GetUserSummaries {
 }
 
 pub fn execute_request(
-    http: &Client,
+    http: &SteamHttp,
+    base_url: &str,
     access_token: &str,
     user_ids: &[SteamId],
-) -> Result<Vec<SteamUser>, reqwest::Error> {
-    const URL: &str = "https://api.steampowered.com/ISteamUserOAuth/GetUserSummaries/v1";
-    http.get(URL)
-        .query(&[
-            ("access_token", access_token),
-            (
-                "steamids",
-                &user_ids
-                    .iter()
-                    .map(ToString::to_string)
-                    .collect::<Vec<String>>()
-                    .join(","),
-            ),
-        ])
-        .send()?
-        .json::<GetUserSummaries>()
+) -> Result<Vec<SteamUser>, SteamError> {
+    const PATH: &str = "/ISteamUserOAuth/GetUserSummaries/v1";
+    let request = http.get(format!("{base_url}{PATH}")).query(&[
+        ("access_token", access_token),
+        (
+            "steamids",
+            &user_ids
+                .iter()
+                .map(ToString::to_string)
+                .collect::<Vec<String>>()
+                .join(","),
+        ),
+    ]);
+    http.send(request)
+        .and_then(parse_response::<GetUserSummaries>)
         .map(|res| res.players)
 }
@@ -1,19 +1,36 @@
+mod cache;
 mod client;
+mod endpoints;
+mod error;
 mod get_current_user_country;
 mod get_friend_list;
+mod get_player_achievements;
+mod get_player_bans;
+mod get_recently_played_games;
+mod get_steam_level;
 mod get_user_summaries;
+mod retry;
 mod store_item;
 mod store_item_user_details;
 mod store_search;
+mod traffic;
 mod user;
 
 use std::sync::LazyLock;
 
+pub use cache::*;
 use chrono::{DateTime, Datelike, Utc};
 pub use client::*;
+pub use endpoints::*;
+pub use error::*;
 pub use get_friend_list::*;
+pub use get_player_achievements::PlayerAchievements;
+pub use get_player_bans::PlayerBans;
+pub use get_recently_played_games::RecentPlaytime;
+pub use retry::*;
 pub use store_item::*;
 pub use store_item_user_details::*;
+pub use traffic::*;
 pub use user::*;
 
 pub static STEAM_FOUNDATION_DATE: LazyLock<DateTime<Utc>> = LazyLock::new(|| {
@@ -0,0 +1,136 @@
+use std::{
+    sync::{Condvar, Mutex},
+    thread,
+    time::{Duration, Instant},
+};
+
+use rand::Rng;
+use reqwest::StatusCode;
+
+use super::SteamError;
+
+/// Exponential backoff with jitter for rate limits, server errors and network failures
+#[derive(Debug, Clone, Copy)]
+pub struct RetryPolicy {
+    pub max_retries: u32,
+    pub base_delay: Duration,
+    pub max_delay: Duration,
+}
+
+impl Default for RetryPolicy {
+    #[inline]
+    fn default() -> Self {
+        Self {
+            max_retries: 4,
+            base_delay: Duration::from_millis(500),
+            max_delay: Duration::from_secs(60),
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// Delay before the next attempt, `None` if the error should not be retried
+    pub fn delay(&self, attempt: u32, err: &SteamError) -> Option<Duration> {
+        if attempt >= self.max_retries {
+            return None;
+        }
+        match err {
+            SteamError::RateLimited {
+                retry_after: Some(secs),
+            } => Some(Duration::from_secs(*secs).min(self.max_delay)),
+            SteamError::RateLimited { retry_after: None } => Some(self.backoff(attempt)),
+            SteamError::Status(status)
+                if status.is_server_error() || *status == StatusCode::REQUEST_TIMEOUT =>
+            {
+                Some(self.backoff(attempt))
+            }
+            SteamError::Request(err) if err.is_timeout() || err.is_connect() => {
+                Some(self.backoff(attempt))
+            }
+            _ => None,
+        }
+    }
+
+    /// Random delay between the half and the whole of `base_delay * 2^attempt`
+    fn backoff(&self, attempt: u32) -> Duration {
+        let delay = self
+            .base_delay
+            .saturating_mul(2u32.saturating_pow(attempt))
+            .min(self.max_delay);
+        delay.mul_f64(rand::rng().random_range(0.5..=1.))
+    }
+}
+
+/// Cap on requests sent at the same time
+pub struct ConcurrencyLimit {
+    max: usize,
+    running: Mutex<usize>,
+    released: Condvar,
+}
+
+pub struct ConcurrencyPermit<'a>(&'a ConcurrencyLimit);
+
+impl ConcurrencyLimit {
+    #[inline]
+    pub fn new(max: usize) -> Self {
+        Self {
+            max: max.max(1),
+            running: Mutex::new(0),
+            released: Condvar::new(),
+        }
+    }
+
+    /// Blocks until the number of running requests is under the cap
+    pub fn acquire(&self) -> ConcurrencyPermit<'_> {
+        let mut running = self
+            .released
+            .wait_while(self.running.lock().unwrap(), |running| *running >= self.max)
+            .unwrap();
+        *running += 1;
+        ConcurrencyPermit(self)
+    }
+}
+
+impl Drop for ConcurrencyPermit<'_> {
+    fn drop(&mut self) {
+        *self.0.running.lock().unwrap() -= 1;
+        self.0.released.notify_one();
+    }
+}
+
+/// Time until which requests wait on a rate limit
+#[derive(Default)]
+pub struct RateLimitWait(Mutex<Option<Instant>>);
+
+impl RateLimitWait {
+    /// Sleeps for `delay`, meanwhile `remaining` reports the wait
+    pub fn sleep(&self, delay: Duration) {
+        let until = Instant::now() + delay;
+        {
+            let mut wait = self.0.lock().unwrap();
+            if wait.is_none_or(|wait| wait < until) {
+                *wait = Some(until);
+            }
+        }
+        thread::sleep(delay);
+        let mut wait = self.0.lock().unwrap();
+        if wait.is_some_and(|wait| wait <= Instant::now()) {
+            *wait = None;
+        }
+    }
+
+    /// Blocks until the wait on a rate limit is over, so no request is sent meanwhile
+    pub fn wait(&self) {
+        while let Some(remaining) = self.remaining() {
+            thread::sleep(remaining);
+        }
+    }
+
+    /// `None` if no request is waiting on a rate limit
+    pub fn remaining(&self) -> Option<Duration> {
+        self.0
+            .lock()
+            .unwrap()
+            .and_then(|wait| wait.checked_duration_since(Instant::now()))
+    }
+}
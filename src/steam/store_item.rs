@@ -1,10 +1,10 @@
-use serde::{Deserialize, Deserializer, de};
+use serde::{Deserialize, Deserializer, Serialize, de};
 use serde_json::Value;
 use std::{fmt::Display, hash::Hash, ops::Deref};
 
 use super::StoreItemUserDetails;
 
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Price {
     /// ISO4217 code
     pub currency: String,
@@ -13,7 +13,7 @@ pub struct Price {
     pub value_in_cents: u32,
 }
 
-#[derive(Debug, Default, Hash, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[derive(Debug, Default, Hash, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(transparent)]
 #[repr(transparent)]
 pub struct StoreItemId(#[serde(deserialize_with = "deserialize_u32")] pub u32);
@@ -53,7 +53,7 @@ impl From<u32> for StoreItemId {
     }
 }
 
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum StoreItemKind {
     #[serde(rename = "app")]
     App,
@@ -63,7 +63,7 @@ pub enum StoreItemKind {
     Unknown,
 }
 
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SteamStoreItem {
     #[serde(rename = "type")]
     pub kind: StoreItemKind,
@@ -1,8 +1,7 @@
 use std::collections::HashMap;
 
-use super::{SteamId, StoreItemId};
-use reqwest::blocking::Client;
-use serde::{Deserialize, Deserializer};
+use super::{SteamError, SteamHttp, SteamId, StoreItemId, parse_response};
+use serde::{Deserialize, Deserializer, Serialize};
 
 #[derive(Debug, Deserialize)]
 struct Response {
@@ -15,7 +14,7 @@ struct DataWrapper {
     data: Option<StoreItemUserDetails>,
 }
 
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct FriendOwn {
     #[serde(rename = "steamid")]
     pub id: SteamId,
@@ -23,18 +22,76 @@ pub struct FriendOwn {
     pub playtime_total: u32,    // in minutes
 }
 
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct FriendWant {
     #[serde(rename = "steamid")]
     pub id: SteamId,
 }
 
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct StoreItemUserDetails {
-    #[serde(rename = "friendsown", default)]
-    pub friends_own: Vec<FriendOwn>,
-    #[serde(rename = "friendswant", default)]
-    pub friends_want: Vec<FriendWant>,
+    #[serde(rename = "friendsown", default, with = "friends_by_id")]
+    pub friends_own: HashMap<SteamId, FriendOwn>,
+    #[serde(rename = "friendswant", default, with = "friends_by_id")]
+    pub friends_want: HashMap<SteamId, FriendWant>,
+}
+
+impl StoreItemUserDetails {
+    #[inline]
+    pub fn is_owned_by(&self, user_id: SteamId) -> bool {
+        self.friends_own.contains_key(&user_id)
+    }
+
+    #[inline]
+    pub fn is_wanted_by(&self, user_id: SteamId) -> bool {
+        self.friends_want.contains_key(&user_id)
+    }
+}
+
+trait FriendEntry {
+    fn id(&self) -> SteamId;
+}
+
+impl FriendEntry for FriendOwn {
+    #[inline]
+    fn id(&self) -> SteamId {
+        self.id
+    }
+}
+
+impl FriendEntry for FriendWant {
+    #[inline]
+    fn id(&self) -> SteamId {
+        self.id
+    }
+}
+
+/// Friends are listed in responses and indexed by id in memory
+mod friends_by_id {
+    use std::collections::HashMap;
+
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    use super::{FriendEntry, SteamId};
+
+    pub fn serialize<S, T>(friends: &HashMap<SteamId, T>, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+        T: Serialize,
+    {
+        serializer.collect_seq(friends.values())
+    }
+
+    pub fn deserialize<'de, D, T>(deserializer: D) -> Result<HashMap<SteamId, T>, D::Error>
+    where
+        D: Deserializer<'de>,
+        T: Deserialize<'de> + FriendEntry,
+    {
+        Ok(Vec::<T>::deserialize(deserializer)?
+            .into_iter()
+            .map(|friend| (friend.id(), friend))
+            .collect())
+    }
 }
 
 fn deserialize_userdetails<'de, D>(
@@ -53,20 +110,20 @@ where
 }
 
 pub fn execute_request(
-    http: &Client,
+    http: &SteamHttp,
+    base_url: &str,
     app_ids: &[StoreItemId],
-) -> Result<HashMap<StoreItemId, Option<StoreItemUserDetails>>, reqwest::Error> {
-    const URL: &str = "https://store.steampowered.com/api/appuserdetails";
-    http.get(URL)
-        .query(&[(
-            "appids",
-            app_ids
-                .iter()
-                .map(ToString::to_string)
-                .collect::<Vec<String>>()
-                .join(","),
-        )])
-        .send()?
-        .json::<Response>()
+) -> Result<HashMap<StoreItemId, Option<StoreItemUserDetails>>, SteamError> {
+    const PATH: &str = "/api/appuserdetails";
+    let request = http.get(format!("{base_url}{PATH}")).query(&[(
+        "appids",
+        app_ids
+            .iter()
+            .map(ToString::to_string)
+            .collect::<Vec<String>>()
+            .join(","),
+    )]);
+    http.send(request)
+        .and_then(parse_response::<Response>)
         .map(|res| res.apps)
 }
@@ -1,7 +1,6 @@
-use reqwest::blocking::Client;
 use serde::Deserialize;
 
-use super::store_item::SteamStoreItem;
+use super::{SteamError, SteamHttp, parse_response, store_item::SteamStoreItem};
 
 #[derive(Debug, Deserialize)]
 struct StoreSearch {
@@ -10,18 +9,18 @@ struct StoreSearch {
 }
 
 pub fn execute_request(
-    http: &Client,
+    http: &SteamHttp,
+    base_url: &str,
     term: &str,
     country_code: Option<&str>,
-) -> Result<Vec<SteamStoreItem>, reqwest::Error> {
-    const URL: &str = "https://store.steampowered.com/api/storesearch";
-    http.get(URL)
-        .query(&[
-            ("term", term),
-            ("l", "english"),
-            ("cc", country_code.unwrap_or("us")),
-        ])
-        .send()?
-        .json::<StoreSearch>()
+) -> Result<Vec<SteamStoreItem>, SteamError> {
+    const PATH: &str = "/api/storesearch";
+    let request = http.get(format!("{base_url}{PATH}")).query(&[
+        ("term", term),
+        ("l", "english"),
+        ("cc", country_code.unwrap_or("us")),
+    ]);
+    http.send(request)
+        .and_then(parse_response::<StoreSearch>)
         .map(|res| res.items)
 }
@@ -0,0 +1,341 @@
+use std::{env, fs, io, path::PathBuf, thread};
+
+use reqwest::{
+    StatusCode, Url,
+    blocking::{Client, Request, RequestBuilder, Response},
+};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use super::{ConcurrencyLimit, RateLimitWait, RetryPolicy, SteamError};
+
+/// Where responses of Steam APIs come from, can be chosen
+/// with `STEAM_TRAFFIC_RECORD` or `STEAM_TRAFFIC_REPLAY` environment variables
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub enum TrafficMode {
+    #[default]
+    Live,
+    /// Requests are sent and their responses are written to the directory
+    Record(PathBuf),
+    /// Responses are read from the directory written in `Record` mode, without network
+    Replay(PathBuf),
+}
+
+impl TrafficMode {
+    pub fn from_env() -> Self {
+        let dir = |name: &str| {
+            env::var_os(name)
+                .filter(|dir| !dir.is_empty())
+                .map(PathBuf::from)
+        };
+        if let Some(dir) = dir("STEAM_TRAFFIC_REPLAY") {
+            Self::Replay(dir)
+        } else if let Some(dir) = dir("STEAM_TRAFFIC_RECORD") {
+            Self::Record(dir)
+        } else {
+            Self::Live
+        }
+    }
+}
+
+/// Recorded exchange, access token is removed from the request
+#[derive(Debug, Serialize, Deserialize)]
+struct Recording {
+    method: String,
+    url: String,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    form: String,
+    status: u16,
+    body: String,
+}
+
+/// HTTP client which sends requests according to `TrafficMode` and `RetryPolicy`
+pub struct SteamHttp {
+    client: Client,
+    pub traffic: TrafficMode,
+    pub retry: RetryPolicy,
+    limit: ConcurrencyLimit,
+    pub rate_limit: RateLimitWait,
+}
+
+impl SteamHttp {
+    /// Requests sent at the same time by all threads
+    const MAX_CONCURRENT_REQUESTS: usize = 4;
+
+    #[inline]
+    pub fn new(client: Client, traffic: TrafficMode) -> Self {
+        Self {
+            client,
+            traffic,
+            retry: Default::default(),
+            limit: ConcurrencyLimit::new(Self::MAX_CONCURRENT_REQUESTS),
+            rate_limit: Default::default(),
+        }
+    }
+
+    #[inline]
+    pub fn get(&self, url: String) -> RequestBuilder {
+        self.client.get(url)
+    }
+
+    #[inline]
+    pub fn post(&self, url: String) -> RequestBuilder {
+        self.client.post(url)
+    }
+
+    /// Sends request and retries it on rate limits, server errors and network failures
+    pub fn send(&self, request: RequestBuilder) -> Result<Response, SteamError> {
+        let request = request.build()?;
+        if let TrafficMode::Replay(_) = self.traffic {
+            // recorded responses do not change, so there is no point to retry them
+            return self.finish(self.execute(request));
+        }
+
+        let mut attempt = 0;
+        loop {
+            // rate limit applies to the whole client, not only to the request which hit it
+            self.rate_limit.wait();
+            let Some(attempt_request) = request.try_clone() else {
+                let _permit = self.limit.acquire();
+                return self.finish(self.execute(request));
+            };
+            let result = {
+                let _permit = self.limit.acquire();
+                self.execute(attempt_request)
+            };
+            let delay = match result {
+                Ok((ref response, _)) => SteamError::from_response(response)
+                    .and_then(|err| self.retry.delay(attempt, &err)),
+                Err(ref err) => self.retry.delay(attempt, err),
+            };
+            let Some(delay) = delay else {
+                return self.finish(result);
+            };
+            if matches!(result, Ok((ref response, _)) if response.status() == StatusCode::TOO_MANY_REQUESTS)
+            {
+                self.rate_limit.sleep(delay);
+            } else {
+                thread::sleep(delay);
+            }
+            attempt += 1;
+        }
+    }
+
+    /// Response of one attempt, in `Record` mode with its recording
+    fn execute(&self, request: Request) -> Result<(Response, Option<Recording>), SteamError> {
+        match self.traffic {
+            TrafficMode::Live => Ok((self.client.execute(request)?, None)),
+            TrafficMode::Record(_) => {
+                let mut recording = Recording::of(&request);
+                let response = self.client.execute(request)?;
+                let status = response.status();
+                let headers = response.headers().clone();
+                let body = response.bytes()?;
+                recording.status = status.as_u16();
+                recording.body = String::from_utf8_lossy(&body).into_owned();
+
+                let mut response = http::Response::builder().status(status);
+                if let Some(response_headers) = response.headers_mut() {
+                    *response_headers = headers;
+                }
+                let response = response
+                    .body(body.to_vec())
+                    .map_err(io::Error::other)?
+                    .into();
+                Ok((response, Some(recording)))
+            }
+            TrafficMode::Replay(ref dir) => {
+                let recording = Recording::of(&request);
+                let path = dir.join(recording.file_name());
+                if !path.exists() {
+                    return Err(SteamError::NotRecorded(recording.url));
+                }
+                let recording: Recording = serde_json::from_slice(&fs::read(path)?)?;
+                let response = http::Response::builder()
+                    .status(StatusCode::from_u16(recording.status).unwrap_or_default())
+                    .body(recording.body.into_bytes())
+                    .map_err(io::Error::other)?
+                    .into();
+                Ok((response, None))
+            }
+        }
+    }
+
+    /// Writes recording of the final attempt, so retried attempts do not overwrite each other
+    fn finish(
+        &self,
+        result: Result<(Response, Option<Recording>), SteamError>,
+    ) -> Result<Response, SteamError> {
+        let (response, recording) = result?;
+        if let (TrafficMode::Record(dir), Some(recording)) = (&self.traffic, recording) {
+            fs::create_dir_all(dir)?;
+            fs::write(
+                dir.join(recording.file_name()),
+                serde_json::to_vec_pretty(&recording)?,
+            )?;
+        }
+        Ok(response)
+    }
+}
+
+impl Recording {
+    fn of(request: &Request) -> Self {
+        let mut url = request.url().clone();
+        let query = url
+            .query_pairs()
+            .filter(|(key, _)| key != "access_token")
+            .map(|(key, value)| (key.into_owned(), value.into_owned()))
+            .collect::<Vec<_>>();
+        if query.is_empty() {
+            url.set_query(None);
+        } else {
+            url.query_pairs_mut().clear().extend_pairs(query);
+        }
+        let form = request
+            .body()
+            .and_then(|body| body.as_bytes())
+            .map(|body| {
+                String::from_utf8_lossy(body)
+                    .split('&')
+                    .filter(|pair| !pair.starts_with("access_token="))
+                    .collect::<Vec<_>>()
+                    .join("&")
+            })
+            .unwrap_or_default();
+        Self {
+            method: request.method().to_string(),
+            url: url.to_string(),
+            form,
+            status: 0,
+            body: String::new(),
+        }
+    }
+
+    /// Name of the endpoint and hash of the request, e.g. `GetFriendList-0123456789abcdef.json`
+    fn file_name(&self) -> String {
+        let url = Url::parse(&self.url).ok();
+        let endpoint = url
+            .as_ref()
+            .and_then(|url| {
+                url.path_segments()?
+                    .rfind(|segment| !segment.is_empty() && !is_version(segment))
+                    .map(str::to_owned)
+            })
+            .unwrap_or_else(|| "request".to_owned());
+        // base URL is not hashed, so recordings can be replayed with other endpoints
+        let request = url
+            .map(|url| format!("{}?{}", url.path(), url.query().unwrap_or_default()))
+            .unwrap_or_else(|| self.url.clone());
+        let hash = Sha256::digest(format!("{} {request}\n{}", self.method, self.form));
+        let hash: String = hash[..8].iter().map(|b| format!("{b:02x}")).collect();
+        format!("{endpoint}-{hash}.json")
+    }
+}
+
+/// Version segment of path, e.g. `v1`
+#[inline]
+fn is_version(segment: &str) -> bool {
+    segment
+        .strip_prefix('v')
+        .is_some_and(|number| number.chars().all(|c| c.is_ascii_digit()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FRIEND_LIST_URL: &str = "https://api.steampowered.com/ISteamUser/GetFriendList/v1/";
+
+    /// Empty directory in temp dir, unique for the test
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = env::temp_dir().join(format!("steam-traffic-{}-{name}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        dir
+    }
+
+    fn recording_of(request: RequestBuilder) -> Recording {
+        Recording::of(&request.build().unwrap())
+    }
+
+    #[test]
+    fn recording_drops_access_token() {
+        let client = Client::new();
+
+        let recording =
+            recording_of(client.get(format!("{FRIEND_LIST_URL}?access_token=secret&steamid=1")));
+        assert_eq!(recording.method, "GET");
+        assert_eq!(recording.url, format!("{FRIEND_LIST_URL}?steamid=1"));
+
+        let recording = recording_of(client.get(format!("{FRIEND_LIST_URL}?access_token=secret")));
+        assert_eq!(recording.url, FRIEND_LIST_URL);
+
+        let recording = recording_of(
+            client
+                .post(FRIEND_LIST_URL)
+                .form(&[("access_token", "secret"), ("steamid", "1")]),
+        );
+        assert_eq!(recording.method, "POST");
+        assert_eq!(recording.form, "steamid=1");
+    }
+
+    #[test]
+    fn file_name_is_endpoint_and_request_hash() {
+        let client = Client::new();
+        let name = recording_of(client.get(format!("{FRIEND_LIST_URL}?steamid=1"))).file_name();
+        assert!(name.starts_with("GetFriendList-"));
+        assert!(name.ends_with(".json"));
+
+        // base URL and access token are not part of the request
+        let local = recording_of(client.get(
+            "http://localhost:8080/ISteamUser/GetFriendList/v1/?access_token=secret&steamid=1",
+        ));
+        assert_eq!(local.file_name(), name);
+
+        let other = recording_of(client.get(format!("{FRIEND_LIST_URL}?steamid=2")));
+        assert_ne!(other.file_name(), name);
+        let post = recording_of(client.post(format!("{FRIEND_LIST_URL}?steamid=1")));
+        assert_ne!(post.file_name(), name);
+
+        let root = recording_of(client.get("https://api.steampowered.com/v1/"));
+        assert!(root.file_name().starts_with("request-"));
+    }
+
+    #[test]
+    fn recorded_response_is_replayed() {
+        let dir = temp_dir("replay");
+        let recorder = SteamHttp::new(Client::new(), TrafficMode::Record(dir.clone()));
+        let mut recording =
+            recording_of(recorder.get(format!("{FRIEND_LIST_URL}?access_token=old&steamid=1")));
+        recording.status = 200;
+        recording.body = r#"{"friendslist":{"friends":[]}}"#.to_owned();
+        let response: Response = http::Response::builder()
+            .status(recording.status)
+            .body(recording.body.clone().into_bytes())
+            .unwrap()
+            .into();
+        recorder.finish(Ok((response, Some(recording)))).unwrap();
+
+        let replayer = SteamHttp::new(Client::new(), TrafficMode::Replay(dir.clone()));
+        let response = replayer
+            .send(replayer.get(format!("{FRIEND_LIST_URL}?access_token=new&steamid=1")))
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(
+            response.text().unwrap(),
+            r#"{"friendslist":{"friends":[]}}"#
+        );
+
+        let _ = fs::remove_dir_all(dir);
+    }
+
+    #[test]
+    fn replay_of_not_recorded_request_fails() {
+        let dir = temp_dir("not-recorded");
+        let replayer = SteamHttp::new(Client::new(), TrafficMode::Replay(dir));
+        let result = replayer.send(replayer.get(format!("{FRIEND_LIST_URL}?steamid=1")));
+        assert!(matches!(
+            result,
+            Err(SteamError::NotRecorded(url)) if url == format!("{FRIEND_LIST_URL}?steamid=1")
+        ));
+    }
+}
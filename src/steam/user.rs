@@ -1,6 +1,6 @@
 use chrono::serde::ts_seconds_option;
-use chrono::{DateTime, Utc};
-use eframe::egui::{Context, CursorIcon, Image, OpenUrl, Sense};
+use chrono::{DateTime, Local, Utc};
+use eframe::egui::{Color32, Context, CursorIcon, Image, OpenUrl, Sense, Ui};
 use egui_extras::TableRow;
 use serde::{Deserialize, Deserializer, Serialize, de};
 use serde_json::Value;
@@ -8,9 +8,11 @@ use std::{fmt::Display, ops::Deref};
 
 use crate::{
     app::{Preferences, Winners},
-    utils::pluralize,
+    utils::{format_age, pluralize},
 };
 
+use super::PlayerBans;
+
 #[derive(Debug, Default, Hash, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(transparent)]
 #[repr(transparent)]
@@ -45,7 +47,7 @@ impl From<SteamId> for u64 {
     }
 }
 
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SteamUser {
     #[serde(rename = "steamid")]
     pub id: SteamId,
@@ -61,13 +63,95 @@ pub struct SteamUser {
 
     #[serde(default, rename = "timecreated", with = "ts_seconds_option")]
     pub created_at: Option<DateTime<Utc>>,
+
+    /// Missing due to privacy settings
+    #[serde(default, rename = "lastlogoff", with = "ts_seconds_option")]
+    pub last_logoff: Option<DateTime<Utc>>,
+    /// Offline if profile is private
+    #[serde(default, rename = "personastate")]
+    pub persona_state: PersonaState,
+    /// Name of the game which is being played
+    #[serde(default, rename = "gameextrainfo")]
+    pub playing_game: Option<String>,
+
+    /// Taken from friend list, summaries have no such field
+    #[serde(default, with = "ts_seconds_option")]
+    pub friend_since: Option<DateTime<Utc>>,
+
+    /// Loaded after summaries, `None` until loaded or if hidden by privacy settings
+    #[serde(default)]
+    pub steam_level: Option<u32>,
+
+    /// Loaded along with summaries, `None` if failed to load
+    #[serde(default)]
+    pub bans: Option<PlayerBans>,
+}
+
+/// Status of user in Steam, when summaries were fetched
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(from = "u8", into = "u8")]
+pub enum PersonaState {
+    #[default]
+    Offline,
+    Online,
+    Busy,
+    Away,
+    Snooze,
+    LookingToTrade,
+    LookingToPlay,
+}
+
+impl From<u8> for PersonaState {
+    fn from(value: u8) -> Self {
+        match value {
+            1 => Self::Online,
+            2 => Self::Busy,
+            3 => Self::Away,
+            4 => Self::Snooze,
+            5 => Self::LookingToTrade,
+            6 => Self::LookingToPlay,
+            _ => Self::Offline,
+        }
+    }
+}
+
+impl From<PersonaState> for u8 {
+    fn from(value: PersonaState) -> Self {
+        value as u8
+    }
+}
+
+impl Display for PersonaState {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Self::Offline => "Offline",
+            Self::Online => "Online",
+            Self::Busy => "Busy",
+            Self::Away => "Away",
+            Self::Snooze => "Snooze",
+            Self::LookingToTrade => "Looking to trade",
+            Self::LookingToPlay => "Looking to play",
+        })
+    }
 }
 
 impl SteamUser {
+    #[inline]
+    pub fn is_online(&self) -> bool {
+        self.persona_state != PersonaState::Offline
+    }
+
+    #[inline]
+    pub fn is_in_game(&self) -> bool {
+        self.playing_game.is_some()
+    }
+
+    /// Status is shown only if `presence_is_fresh`
     pub fn add_to_table_row(
         &self,
         preferences: &Preferences,
         winners: &Winners,
+        presence_is_fresh: bool,
         ctx: &Context,
         row: &mut TableRow,
     ) {
@@ -88,6 +172,9 @@ impl SteamUser {
         }
         row.col(|ui| {
             ui.horizontal_centered(|ui| {
+                if presence_is_fresh {
+                    self.status_ui(ui);
+                }
                 let name_ui = ui
                     .label(&self.name)
                     .on_hover_cursor(CursorIcon::Copy)
@@ -131,6 +218,26 @@ impl SteamUser {
                 }
             });
         });
+        row.col(|ui| {
+            ui.centered_and_justified(|ui| {
+                if let Some(level) = self.steam_level {
+                    ui.label(level.to_string());
+                } else {
+                    ui.label("?")
+                        .on_hover_text_at_pointer("Not loaded yet or due to privacy settings");
+                }
+            });
+        });
+        row.col(|ui| {
+            ui.centered_and_justified(|ui| {
+                if let Some(since) = self.friend_since {
+                    ui.label(since.with_timezone(&Local).format("%b %d, %Y").to_string());
+                } else {
+                    ui.label("?")
+                        .on_hover_text_at_pointer("Unknown, friends list needs to be reloaded");
+                }
+            });
+        });
         row.col(|ui| {
             ui.centered_and_justified(|ui| {
                 ui.label(if let Some(times) = winners.all_time.get(&self.id) {
@@ -141,6 +248,33 @@ impl SteamUser {
             });
         });
     }
+
+    /// Dot colored like in Steam friends list
+    fn status_ui(&self, ui: &mut Ui) {
+        let (color, hover_text) = if let Some(ref game) = self.playing_game {
+            (Color32::from_rgb(144, 186, 60), format!("In game: {game}"))
+        } else if self.is_online() {
+            (
+                Color32::from_rgb(87, 203, 222),
+                self.persona_state.to_string(),
+            )
+        } else {
+            (
+                ui.style().visuals.weak_text_color(),
+                match self.last_logoff {
+                    Some(last_logoff) => {
+                        format!(
+                            "Offline, last online {}",
+                            format_age(Utc::now() - last_logoff)
+                        )
+                    }
+                    None => "Offline".to_owned(),
+                },
+            )
+        };
+        ui.colored_label(color, "\u{25CF}")
+            .on_hover_text_at_pointer(hover_text);
+    }
 }
 
 /// deserialize number or string to u64
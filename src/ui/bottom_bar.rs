@@ -1,6 +1,10 @@
+use chrono::{Local, Utc};
 use eframe::egui::{Align, Context, DragValue, Layout, TopBottomPanel};
 
-use crate::{app::App, utils::pluralize};
+use crate::{
+    app::App,
+    utils::{format_age, pluralize},
+};
 
 use super::FRIENDS_PER_PAGE;
 
@@ -35,6 +39,22 @@ pub fn bottom_bar(app: &mut App, ctx: &Context) {
                     });
                 }
                 ui.with_layout(Layout::right_to_left(Align::Center), |ui| {
+                    if let Some(fetched_at) = app.friends.fetched_at {
+                        ui.label(format!(
+                            "\u{1F551} {}{}",
+                            format_age(Utc::now() - fetched_at),
+                            if app.friends.is_loading {
+                                ", refreshing..."
+                            } else {
+                                ""
+                            }
+                        ))
+                        .on_hover_text_at_pointer(format!(
+                            "Friends data was fetched from Steam on {}",
+                            fetched_at.with_timezone(&Local).format("%b %d, %Y, %H:%M")
+                        ));
+                        ui.separator();
+                    }
                     let friends_all_count = app.friends.all.len();
                     ui.label(if friends_all_count > 0 {
                         let mut txt =
@@ -1,8 +1,9 @@
 use eframe::egui::{Align, CentralPanel, Context, Layout, TextEdit};
-use egui_extras::{Column, TableBuilder};
+use egui_extras::{Column, TableBuilder, TableRow};
 
 use crate::{
     app::{App, Msg},
+    steam::SteamUser,
     utils::pluralize,
 };
 
@@ -32,15 +33,29 @@ pub fn central_panel(app: &mut App, ctx: &Context) {
         .response
         .on_disabled_hover_text("No friends to search.");
         ui.add_space(7.);
+        app.friends.update_filtered(
+            &app.filters,
+            app.filters_revision,
+            &app.winners,
+            app.store_item_for_giveaway.as_ref(),
+            &app.sender,
+        );
+        let show_chances = app.winners.weights.enabled;
         let mut table = TableBuilder::new(ui).striped(true).auto_shrink(false);
         if app.preferences.avatars {
             table = table.column(Column::auto().at_least(40.));
         }
-        table
+        table = table
             .column(Column::remainder().at_least(200.))
             .column(Column::auto().at_least(60.))
             .column(Column::auto())
             .column(Column::auto())
+            .column(Column::auto())
+            .column(Column::auto());
+        if show_chances {
+            table = table.column(Column::auto().at_least(60.));
+        }
+        table
             .cell_layout(Layout::left_to_right(Align::Center))
             .header(20.0, |mut header| {
                 if app.preferences.avatars {
@@ -64,9 +79,23 @@ pub fn central_panel(app: &mut App, ctx: &Context) {
                         .on_hover_text_at_pointer("How old is the account in years");
                     ui.add_space(10.);
                 });
+                header.col(|ui| {
+                    ui.heading("\u{2B50} Level")
+                        .on_hover_text_at_pointer("Steam level");
+                });
+                header.col(|ui| {
+                    ui.heading("\u{1F91D} Since")
+                        .on_hover_text_at_pointer("Since when you are friends");
+                });
                 header.col(|ui| {
                     ui.heading("\u{1F3C6} Won");
                 });
+                if show_chances {
+                    header.col(|ui| {
+                        ui.heading("\u{2696} Chance")
+                            .on_hover_text_at_pointer("Chance to be among next winners.\nEstimated by simulated draws if there are several winners.");
+                    });
+                }
             })
             .body(|body| {
                 const ROW_HEIGHT: f32 = 32.;
@@ -75,22 +104,34 @@ pub fn central_panel(app: &mut App, ctx: &Context) {
                     return;
                 }
 
+                let presence_is_fresh = app.friends.presence_is_fresh();
+                let chances = &app.friends.chances;
+                let add_chance_col = |row: &mut TableRow, friend: &SteamUser| {
+                    if show_chances {
+                        row.col(|ui| {
+                            ui.centered_and_justified(|ui| {
+                                if chances.is_empty() {
+                                    ui.spinner().on_hover_text_at_pointer("Estimating chances");
+                                } else {
+                                    ui.label(format!(
+                                        "{:.2}%",
+                                        chances.get(&friend.id).copied().unwrap_or_default() * 100.
+                                    ));
+                                }
+                            });
+                        });
+                    }
+                };
+
                 if !app.friends_search_name.is_empty() {
                     body.rows(ROW_HEIGHT, app.friends_search_results.len(), |mut row| {
                         let friend = &app.friends_search_results[row.index()];
-                        friend.add_to_table_row(&app.preferences, &app.winners, ctx, &mut row);
+                        friend.add_to_table_row(&app.preferences, &app.winners, presence_is_fresh, ctx, &mut row);
+                        add_chance_col(&mut row, friend);
                     });
                     return;
                 }
 
-                app.friends.update_filtered(
-                    &app.filters,
-                    &app.winners,
-                    app.store_item_for_giveaway
-                        .as_ref()
-                        .map(|app| app.user_details.as_ref())
-                        .unwrap_or_default(),
-                );
                 let total_pages = app.friends.filtered.len().div_ceil(FRIENDS_PER_PAGE).max(1);
                 app.main_current_page = app.main_current_page.min(total_pages);
 
@@ -99,8 +140,9 @@ pub fn central_panel(app: &mut App, ctx: &Context) {
 
                 let filtered_friends_page = &app.friends.filtered[start..end];
                 body.rows(ROW_HEIGHT, filtered_friends_page.len(), |mut row| {
-                    let friend = &filtered_friends_page[row.index()];
-                    friend.add_to_table_row(&app.preferences, &app.winners, ctx, &mut row);
+                    let friend = &app.friends.all[filtered_friends_page[row.index()]];
+                    friend.add_to_table_row(&app.preferences, &app.winners, presence_is_fresh, ctx, &mut row);
+                    add_chance_col(&mut row, friend);
                 });
             });
     });
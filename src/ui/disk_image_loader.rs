@@ -0,0 +1,156 @@
+use std::{
+    collections::HashMap,
+    fs,
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex},
+    task::Poll,
+    thread,
+    time::{Duration, SystemTime},
+};
+
+use eframe::egui::{
+    Context,
+    load::{Bytes, BytesLoadResult, BytesLoader, BytesPoll, LoadError},
+};
+
+use crate::steam::{DiskCache, file_key};
+
+type Entry = Poll<Result<(Arc<[u8]>, Option<String>), String>>;
+
+/// Loads images from the web like the default loader, but also keeps them on disk,
+/// so avatars, flags and capsules are not downloaded again on next launches
+pub struct DiskImageLoader {
+    dir: PathBuf,
+    cache: Arc<Mutex<HashMap<String, Entry>>>,
+}
+
+impl DiskImageLoader {
+    pub const ID: &'static str = eframe::egui::generate_loader_id!(DiskImageLoader);
+
+    /// Images older than this are downloaded again
+    const MAX_AGE: Duration = Duration::from_secs(7 * 24 * 60 * 60);
+
+    /// Must be installed after default loaders, so it is tried first
+    pub fn install(ctx: &Context) {
+        if let Some(dir) = Self::dir() {
+            ctx.add_bytes_loader(Arc::new(Self {
+                dir,
+                cache: Default::default(),
+            }));
+        }
+    }
+
+    #[inline]
+    pub fn dir() -> Option<PathBuf> {
+        DiskCache::default_dir().map(|dir| dir.join("images"))
+    }
+
+    /// Removes images from disk, images in memory are forgotten with `Context::forget_all_images`
+    pub fn clear() {
+        if let Some(dir) = Self::dir() {
+            let _ = fs::remove_dir_all(dir);
+        }
+    }
+
+    fn read_from_disk(&self, path: &Path) -> Option<Arc<[u8]>> {
+        let modified = fs::metadata(path).and_then(|meta| meta.modified()).ok()?;
+        if SystemTime::now()
+            .duration_since(modified)
+            .is_ok_and(|age| age > Self::MAX_AGE)
+        {
+            return None;
+        }
+        fs::read(path).ok().map(Into::into)
+    }
+}
+
+impl BytesLoader for DiskImageLoader {
+    fn id(&self) -> &str {
+        Self::ID
+    }
+
+    fn load(&self, ctx: &Context, uri: &str) -> BytesLoadResult {
+        if !uri.starts_with("https://") && !uri.starts_with("http://") {
+            return Err(LoadError::NotSupported);
+        }
+
+        let mut cache = self.cache.lock().unwrap();
+        if let Some(entry) = cache.get(uri).cloned() {
+            return match entry {
+                Poll::Ready(Ok((bytes, mime))) => Ok(BytesPoll::Ready {
+                    size: None,
+                    bytes: Bytes::Shared(bytes),
+                    mime,
+                }),
+                Poll::Ready(Err(err)) => Err(LoadError::Loading(err)),
+                Poll::Pending => Ok(BytesPoll::Pending { size: None }),
+            };
+        }
+
+        let path = self.dir.join(file_key(uri));
+        if let Some(bytes) = self.read_from_disk(&path) {
+            let mime = image_mime(&bytes);
+            cache.insert(
+                uri.to_owned(),
+                Poll::Ready(Ok((bytes.clone(), mime.clone()))),
+            );
+            return Ok(BytesPoll::Ready {
+                size: None,
+                bytes: Bytes::Shared(bytes),
+                mime,
+            });
+        }
+
+        cache.insert(uri.to_owned(), Poll::Pending);
+        drop(cache);
+
+        let uri = uri.to_owned();
+        let dir = self.dir.clone();
+        let cache = self.cache.clone();
+        let ctx = ctx.clone();
+        thread::spawn(move || {
+            let result = reqwest::blocking::get(&uri)
+                .and_then(|response| response.error_for_status())
+                .and_then(|response| response.bytes())
+                .map(|bytes| {
+                    // images are still shown if they can not be saved
+                    let _ = fs::create_dir_all(&dir).and_then(|_| fs::write(&path, &bytes));
+                    let bytes: Arc<[u8]> = bytes.to_vec().into();
+                    let mime = image_mime(&bytes);
+                    (bytes, mime)
+                })
+                .map_err(|err| err.to_string());
+            cache.lock().unwrap().insert(uri, Poll::Ready(result));
+            ctx.request_repaint();
+        });
+
+        Ok(BytesPoll::Pending { size: None })
+    }
+
+    fn forget(&self, uri: &str) {
+        self.cache.lock().unwrap().remove(uri);
+    }
+
+    fn forget_all(&self) {
+        self.cache.lock().unwrap().clear();
+    }
+
+    fn byte_size(&self) -> usize {
+        self.cache
+            .lock()
+            .unwrap()
+            .values()
+            .map(|entry| match entry {
+                Poll::Ready(Ok((bytes, _))) => bytes.len(),
+                _ => 0,
+            })
+            .sum()
+    }
+}
+
+/// Image URLs may have no extension, so format is guessed by content
+fn image_mime(bytes: &[u8]) -> Option<String> {
+    image::guess_format(bytes)
+        .ok()
+        .map(|format| format.to_mime_type().to_owned())
+}
@@ -0,0 +1,58 @@
+use eframe::egui::{Context, Grid, ScrollArea, TextEdit, Window};
+
+use crate::app::App;
+
+pub fn funnel_window(app: &mut App, ctx: &Context) {
+    Window::new("\u{1F53B} Filter funnel")
+        .open(&mut app.show_funnel_window)
+        .order(eframe::egui::Order::Middle)
+        .default_width(300.)
+        .show(ctx, |ui| {
+            Grid::new("filter_funnel")
+                .num_columns(2)
+                .striped(true)
+                .show(ui, |ui| {
+                    for stage in &app.friends.funnel {
+                        ui.label(&stage.filter);
+                        ui.label(stage.left.to_string());
+                        ui.end_row();
+                    }
+                });
+            ui.separator();
+
+            ui.horizontal(|ui| {
+                ui.label("Why not?");
+                ui.add(
+                    TextEdit::singleline(&mut app.why_not_search_term)
+                        .char_limit(100)
+                        .hint_text("Friend name or SteamID..."),
+                );
+            });
+            let term = app.why_not_search_term.trim().to_lowercase();
+            if term.is_empty() {
+                return;
+            }
+            ScrollArea::vertical().max_height(300.).show(ui, |ui| {
+                for friend in app
+                    .friends
+                    .all
+                    .iter()
+                    .filter(|friend| {
+                        friend.name.to_lowercase().contains(&term) || friend.id.to_string() == term
+                    })
+                    .take(50)
+                {
+                    ui.horizontal_wrapped(|ui| {
+                        ui.strong(&friend.name);
+                        match app.friends.exclusions.get(&friend.id) {
+                            Some(exclusion) => ui.label(format!(
+                                "\u{2716} excluded by \"{}\": {}",
+                                exclusion.filter, exclusion.reason
+                            )),
+                            None => ui.label("\u{2714} passes all filters"),
+                        };
+                    });
+                }
+            });
+        });
+}
@@ -0,0 +1,247 @@
+use chrono::Local;
+use eframe::egui::{
+    CollapsingHeader, Context, CursorIcon, OpenUrl, ScrollArea, Sense, TextEdit, Window,
+};
+
+use crate::{
+    app::{App, FilterNodeKind, Filters, FriendFilter, HasAppMode, PresenceFilter, RegionFilter},
+    utils::pluralize,
+};
+
+pub fn history_window(app: &mut App, ctx: &Context) {
+    Window::new(format!(
+        "\u{1F4DC} Giveaways history ({})",
+        app.winners.history.len()
+    ))
+    .open(&mut app.show_history_window)
+    .order(eframe::egui::Order::Middle)
+    .default_width(400.)
+    .show(ctx, |ui| {
+        ui.horizontal(|ui| {
+            ui.label("\u{1F50D}");
+            ui.add(
+                TextEdit::singleline(&mut app.history_search_term)
+                    .char_limit(100)
+                    .hint_text("Search by item or winner name..."),
+            );
+        });
+        ui.separator();
+
+        let mut delete_index = None;
+        ScrollArea::vertical().show(ui, |ui| {
+            let term = app.history_search_term.trim();
+            for (index, record) in app.winners.history.iter().enumerate().rev() {
+                if !term.is_empty() && !record.matches(term) {
+                    continue;
+                }
+                CollapsingHeader::new(format!(
+                    "{} \u{2014} {} \u{2014} {}",
+                    record.date.with_timezone(&Local).format("%b %d, %Y, %H:%M"),
+                    record
+                        .store_item
+                        .as_ref()
+                        .map(|item| item.name.as_str())
+                        .unwrap_or("No item"),
+                    pluralize("winner", record.winners.len()),
+                ))
+                .id_salt(("giveaway_record", index, record.date))
+                .show(ui, |ui| {
+                    if let Some(ref item) = record.store_item {
+                        ui.horizontal(|ui| {
+                            ui.hyperlink_to(&item.name, format!("steam://store/{}", item.id));
+                            if let Some(ref price) = item.price {
+                                ui.label(format!(
+                                    "{} {}",
+                                    price.currency,
+                                    price.value_in_cents as f32 / 100.
+                                ));
+                            }
+                        });
+                    }
+                    ui.label(format!(
+                        "Requested {}",
+                        pluralize("winner", record.winners_requested)
+                    ));
+                    ui.strong("Winners");
+                    for winner in &record.winners {
+                        if ui
+                            .label(&winner.name)
+                            .interact(Sense::click())
+                            .on_hover_cursor(CursorIcon::PointingHand)
+                            .on_hover_text_at_pointer("Open Steam profile")
+                            .clicked()
+                        {
+                            ctx.open_url(OpenUrl::new_tab(format!(
+                                "steam://url/SteamIDPage/{}",
+                                winner.id
+                            )));
+                        }
+                    }
+                    if !record.replaced.is_empty() {
+                        ui.strong("Replaced");
+                        for replaced in &record.replaced {
+                            ui.label(format!(
+                                "{} \u{2014} {}",
+                                replaced.winner.name, replaced.reason
+                            ));
+                        }
+                    }
+                    ui.strong("Filters");
+                    for line in filters_summary(&record.filters) {
+                        ui.label(line);
+                    }
+                    if ui.button("\u{1F5D1} Delete").clicked() {
+                        delete_index = Some(index);
+                    }
+                });
+            }
+        });
+
+        if let Some(index) = delete_index {
+            app.winners.delete_record(index);
+            app.toasts.info("Giveaway record deleted!");
+        }
+    });
+}
+
+fn filters_summary(filters: &Filters) -> Vec<String> {
+    let mut lines = vec![];
+    if filters.account_age > 0 {
+        lines.push(format!("Account age >= {}", filters.account_age));
+    }
+    if filters.exclude_unknown_age {
+        lines.push("Exclude unknown age".to_owned());
+    }
+    if filters.friends_for_days > 0 {
+        lines.push(format!(
+            "Friends for at least {}",
+            pluralize("day", filters.friends_for_days as usize)
+        ));
+    }
+    let level = filters.steam_level;
+    if level.min > 0 || level.max.is_some() || level.exclude_unknown {
+        lines.push(format!("Steam {}", level.name()));
+    }
+    if filters.exclude_community_banned {
+        lines.push("Exclude community banned".to_owned());
+    }
+    if filters.exclude_trade_banned {
+        lines.push("Exclude trade banned".to_owned());
+    }
+    if filters.vac_banned_within_days > 0 {
+        lines.push(format!(
+            "Exclude who got VAC ban in last {}",
+            pluralize("day", filters.vac_banned_within_days as usize)
+        ));
+    }
+    if filters.online_within_days > 0 {
+        lines.push(format!(
+            "Online in last {}",
+            pluralize("day", filters.online_within_days as usize)
+        ));
+    }
+    if filters.presence != PresenceFilter::Any {
+        lines.push(format!("Only {}", filters.presence.name()));
+    }
+    if filters.recent_activity.enabled {
+        lines.push(format!("Only who {}", filters.recent_activity.name()));
+    }
+    if filters.include_who_has_app_in_wishlist {
+        lines.push("Only who has app in wishlist".to_owned());
+    }
+    if filters.exclude_who_owns_app {
+        lines.push("Exclude who already owns app".to_owned());
+    }
+    if filters.exclude_who_won_before {
+        lines.push("Exclude who won earlier".to_owned());
+    }
+    if filters.won_within_days > 0 {
+        lines.push(format!(
+            "Exclude who won in last {}",
+            pluralize("day", filters.won_within_days as usize)
+        ));
+    }
+    if filters.won_within_giveaways > 0 {
+        lines.push(format!(
+            "Exclude who won in last {}",
+            pluralize("giveaway", filters.won_within_giveaways)
+        ));
+    }
+    if filters.max_wins > 0 {
+        lines.push(format!(
+            "Exclude who won >= {}",
+            pluralize("time", filters.max_wins)
+        ));
+    }
+
+    let regions = &filters.regions_and_countries;
+    let mut include = regions.include_countries.clone();
+    let mut exclude = regions.exclude_countries.clone();
+    for (name, filter) in [("Unknown", regions.unknown)].into_iter().chain(
+        regions
+            .groups
+            .iter()
+            .map(|group_filter| (group_filter.group.name.as_str(), group_filter.filter)),
+    ) {
+        match filter {
+            RegionFilter::Available => {}
+            RegionFilter::Include => include.push(name.to_owned()),
+            RegionFilter::Exclude => exclude.push(name.to_owned()),
+        }
+    }
+    if !include.is_empty() {
+        lines.push(format!("Include regions: {}", include.join(", ")));
+    }
+    if !exclude.is_empty() {
+        lines.push(format!("Exclude regions: {}", exclude.join(", ")));
+    }
+
+    for filter in &filters.has_store_items {
+        if let Some(ref app) = filter.app {
+            lines.push(match filter.mode {
+                HasAppMode::Owns => format!(
+                    "Has {} (total {}, 2 weeks {}{})",
+                    app.name,
+                    playtime_bounds(filter.playtime_total, filter.max_playtime_total),
+                    playtime_bounds(
+                        filter.playtime_twoweeks as u32,
+                        filter.max_playtime_twoweeks.map(u32::from)
+                    ),
+                    if filter.achievement_percent > 0 {
+                        format!(
+                            ", achievements >= {}%{}",
+                            filter.achievement_percent,
+                            if filter.include_private_achievements {
+                                " or hidden"
+                            } else {
+                                ""
+                            }
+                        )
+                    } else {
+                        String::new()
+                    },
+                ),
+                HasAppMode::NotOwns => format!("Does not have {}", app.name),
+            });
+        }
+    }
+
+    if let FilterNodeKind::Group { ref children, .. } = filters.tree.kind {
+        if !children.is_empty() {
+            lines.push(format!("Groups: {}", filters.tree));
+        }
+    }
+
+    if lines.is_empty() {
+        lines.push("None".to_owned());
+    }
+    lines
+}
+
+#[inline]
+fn playtime_bounds(min: u32, max: Option<u32>) -> String {
+    match max {
+        Some(max) => format!(">= {min} h. and < {max} h."),
+        None => format!(">= {min} h."),
+    }
+}
@@ -2,7 +2,10 @@ mod top_bar;
 mod side_panel;
 mod central_panel;
 mod bottom_bar;
+mod disk_image_loader;
 mod winners_window;
+mod history_window;
+mod funnel_window;
 mod main_window;
 mod search_select;
 mod settings_window;
@@ -13,8 +16,11 @@ pub use top_bar::*;
 pub use side_panel::*;
 pub use central_panel::*;
 pub use bottom_bar::*;
+pub use disk_image_loader::*;
 pub use main_window::*;
 pub use winners_window::*;
+pub use history_window::*;
+pub use funnel_window::*;
 pub use search_select::*;
 pub use settings_window::*;
 pub use style_override::*;
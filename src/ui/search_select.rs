@@ -8,14 +8,15 @@ use std::{
 
 use eframe::egui::{Button, CursorIcon, Image, ScrollArea, TextEdit, Ui, menu};
 
-use crate::steam::{SteamApiClient, SteamStoreItem, StoreItemKind};
+use crate::steam::{CachedEndpoint, SteamApiClient, SteamError, SteamStoreItem, StoreItemKind};
 
 pub struct SearchSelect {
     term: String,
     results: Vec<SteamStoreItem>,
+    error: Option<SteamError>,
     is_loading: bool,
-    sender: Sender<Vec<SteamStoreItem>>,
-    receiver: Receiver<Vec<SteamStoreItem>>,
+    sender: Sender<Result<Vec<SteamStoreItem>, SteamError>>,
+    receiver: Receiver<Result<Vec<SteamStoreItem>, SteamError>>,
 }
 
 pub struct SearchSelectResponse {
@@ -28,6 +29,7 @@ impl SearchSelect {
         Self {
             term: String::new(),
             results: vec![],
+            error: None,
             is_loading: false,
             sender,
             receiver,
@@ -46,7 +48,16 @@ impl SearchSelect {
 
         if let Ok(results) = self.receiver.try_recv() {
             self.is_loading = false;
-            self.results = results;
+            match results {
+                Ok(results) => {
+                    self.results = results;
+                    self.error = None;
+                }
+                Err(err) => {
+                    self.results = vec![];
+                    self.error = Some(err);
+                }
+            }
         }
 
         let selected_item_clone = selected.clone();
@@ -67,23 +78,35 @@ impl SearchSelect {
                     let sender = self.sender.clone();
                     let term = self.term.clone();
                     thread::spawn(move || {
-                        let _ = sender.send(
-                            steam
-                                .read()
-                                .unwrap()
-                                .store_search(&term, country_code.as_deref())
-                                .map(|res| {
-                                    res.into_iter()
-                                        .filter(|item| matches!(item.kind, StoreItemKind::App))
-                                        .collect()
-                                })
-                                .unwrap_or_default(),
-                        );
+                        let apps = |items: Vec<SteamStoreItem>| {
+                            items
+                                .into_iter()
+                                .filter(|item| matches!(item.kind, StoreItemKind::App))
+                                .collect()
+                        };
+                        let steam = steam.read().unwrap().clone();
+                        let cached = steam.cached_store_search(&term, country_code.as_deref());
+                        if let Some(cached) = cached {
+                            let is_fresh = cached.is_fresh(CachedEndpoint::StoreSearch);
+                            let _ = sender.send(Ok(apps(cached.value)));
+                            if is_fresh {
+                                return;
+                            }
+                            // failed refresh keeps cached results
+                            if let Ok(items) = steam.store_search(&term, country_code.as_deref()) {
+                                let _ = sender.send(Ok(apps(items)));
+                            }
+                        } else {
+                            let _ = sender
+                                .send(steam.store_search(&term, country_code.as_deref()).map(apps));
+                        }
                     });
                 }
                 ui.separator();
                 if self.is_loading {
                     ui.spinner();
+                } else if let Some(ref err) = self.error {
+                    ui.colored_label(ui.visuals().error_fg_color, format!("Search failed: {err}"));
                 } else {
                     ui.label(format!("Results ({}):", self.results.len()));
                     ui.add_space(5.);
@@ -3,7 +3,8 @@ use eframe::egui::{Align, Align2, Color32, Context, Layout, TextEdit, Vec2, Wind
 
 use crate::{
     app::{App, Msg},
-    steam::TokenError,
+    steam::{TokenError, TrafficMode},
+    ui::DiskImageLoader,
     utils::ui_with_space_before_and_after,
 };
 
@@ -95,10 +96,45 @@ pub fn settings_window(app: &mut App, ctx: &Context) {
 
             ui.heading("Other");
             if ui.button("Clear cached images").clicked() {
+                DiskImageLoader::clear();
                 ctx.forget_all_images();
             }
+            if ui
+                .button("Clear cached Steam data")
+                .on_hover_text("Friends, app user details and store search results saved on disk")
+                .clicked()
+            {
+                app.steam.read().unwrap().clear_cache();
+            }
             if ui.button("Clear winners for all time").clicked() {
-                app.winners.all_time = Default::default();
+                app.winners.clear_all_time();
+            }
+
+            let steam = app.steam.read().unwrap();
+            let endpoints = steam.endpoints();
+            let traffic = steam.traffic();
+            if !endpoints.is_default() || *traffic != TrafficMode::Live {
+                ui_with_space_before_and_after(ui, |ui| {
+                    ui.separator();
+                });
+
+                ui.heading("Steam API");
+                if !endpoints.is_default() {
+                    ui.colored_label(ui.style().visuals.warn_fg_color, "Custom endpoints are used \u{26A0}");
+                    ui.label(format!("Web API: {}", endpoints.api));
+                    ui.label(format!("Store: {}", endpoints.store));
+                }
+                match traffic {
+                    TrafficMode::Live => {}
+                    TrafficMode::Record(dir) => {
+                        ui.colored_label(ui.style().visuals.warn_fg_color, "Traffic is recorded \u{23FA}");
+                        ui.label(format!("Directory: {}", dir.display()));
+                    }
+                    TrafficMode::Replay(dir) => {
+                        ui.colored_label(ui.style().visuals.warn_fg_color, "Traffic is replayed, network is not used \u{25B6}");
+                        ui.label(format!("Directory: {}", dir.display()));
+                    }
+                }
             }
         });
 }
@@ -1,18 +1,34 @@
 use chrono::Utc;
-use eframe::egui::{
-    Align, Button, Checkbox, Context, Direction, DragValue, Layout, ProgressBar, RichText,
-    ScrollArea, SidePanel, TopBottomPanel, menu::menu_custom_button,
+use eframe::{
+    egui::{
+        Align, Button, Checkbox, CollapsingHeader, ComboBox, Context, Direction, DragValue, Id,
+        Layout, ProgressBar, RichText, ScrollArea, SidePanel, TextEdit, TopBottomPanel, Ui,
+        menu::menu_custom_button,
+    },
+    emath::Numeric,
 };
 use egui_extras::{Column, TableBuilder};
-use std::thread;
+use std::{
+    fmt::Display,
+    sync::{Arc, RwLock, mpsc::Sender},
+    time::Duration,
+};
 
 use crate::{
-    app::{App, Msg, RegionFilter},
-    steam::STEAM_FOUNDATION_DATE,
-    utils::ui_with_space_before_and_after,
+    app::{
+        App, CountryFilter, DrawWeights, FilterNode, FilterNodeKind, FriendFilter, GroupOperator,
+        HasAppMode, Msg, OnlineWithin, PresenceFilter, RecentActivityFilter, RegionFilter,
+        RegionGroup, TaskToken, VacBannedWithin, load_filter_app_user_details,
+    },
+    steam::{RecentPlaytime, STEAM_FOUNDATION_DATE, SteamApiClient, SteamStoreItem},
+    ui::SearchSelect,
+    utils::{pluralize, ui_with_space_before_and_after},
 };
 
 pub fn side_panel(app: &mut App, ctx: &Context) {
+    let mut filters_changed = false;
+    // weights or number of next winners, chances depend on them
+    let mut draw_changed = false;
     SidePanel::right("side_panel")
         .resizable(false)
         .max_width(270.)
@@ -38,12 +54,28 @@ pub fn side_panel(app: &mut App, ctx: &Context) {
                                 }
                             });
                             if app.friends.is_loading {
-                                ui.add(
-                                    ProgressBar::new(app.friends.loading_progress)
-                                        .desired_height(6.)
-                                        .corner_radius(1)
-                                        .fill(ui.style().visuals.strong_text_color()),
+                                ui.with_layout(Layout::right_to_left(Align::Center), |ui| {
+                                    if ui
+                                        .small_button("\u{2716}")
+                                        .on_hover_text_at_pointer("Cancel loading.\nAlready shown friends are kept.")
+                                        .clicked()
+                                    {
+                                        app.friends.cancel_loading();
+                                    }
+                                    ui.add(
+                                        ProgressBar::new(app.friends.loading_progress)
+                                            .desired_height(6.)
+                                            .corner_radius(1)
+                                            .fill(ui.style().visuals.strong_text_color()),
+                                    );
+                                });
+                            }
+                            if let Some(wait) = app.steam.read().unwrap().rate_limit_wait() {
+                                ui.colored_label(
+                                    ui.style().visuals.warn_fg_color,
+                                    format!("\u{23F3} Rate limited by Steam, retrying in {} s.", wait.as_secs() + 1),
                                 );
+                                ctx.request_repaint_after(Duration::from_millis(250));
                             }
                         });
                     });
@@ -65,6 +97,7 @@ pub fn side_panel(app: &mut App, ctx: &Context) {
                                     .clicked()
                                 {
                                     app.winners.next_number -= 1;
+                                    draw_changed = true;
                                 }
                                 let max_winners = app.friends.filtered.len().max(1);
                                 ui.with_layout(Layout::right_to_left(Align::Center), |ui| {
@@ -76,24 +109,46 @@ pub fn side_panel(app: &mut App, ctx: &Context) {
                                         .clicked()
                                     {
                                         app.winners.next_number += 1;
+                                        draw_changed = true;
                                     }
-                                    ui.add_sized(
+                                    draw_changed |= ui.add_sized(
                                         [ui.available_width(), ROW_HEIGHT],
                                         DragValue::new(&mut app.winners.next_number)
                                             .prefix("Count: ")
                                             .range(1..=max_winners),
                                     )
-                                    .on_hover_text_at_pointer("Number of next winners");
+                                    .on_hover_text_at_pointer("Number of next winners")
+                                    .changed();
                                 });
                             });
 
                             if ui.button("\u{1F3B2} Random new").clicked() {
-                                app.winners.update_current(&app.friends);
-                                app.show_winners_window = true;
-                            }
-                            if ui.button("Show").clicked() {
+                                app.winners.update_current(
+                                    &app.friends,
+                                    app.store_item_for_giveaway.as_ref(),
+                                    &app.filters,
+                                );
                                 app.show_winners_window = true;
                             }
+                            ui.horizontal(|ui| {
+                                ui.scope(|ui| {
+                                    ui.set_width(ui.available_width() / 2.);
+                                    ui.vertical_centered_justified(|ui| {
+                                        if ui.button("Show").clicked() {
+                                            app.show_winners_window = true;
+                                        }
+                                    });
+                                });
+                                ui.vertical_centered_justified(|ui| {
+                                    if ui
+                                        .button("\u{1F4DC} History")
+                                        .on_hover_text_at_pointer("Saved giveaways")
+                                        .clicked()
+                                    {
+                                        app.show_history_window = true;
+                                    }
+                                });
+                            });
                             ui.horizontal(|ui| {
                                 ui.scope(|ui| {
                                     ui.set_width(ui.available_width() / 2.);
@@ -116,6 +171,83 @@ pub fn side_panel(app: &mut App, ctx: &Context) {
                                     }
                                 });
                             });
+                            ui.horizontal(|ui| {
+                                let mut verifiable = app.winners.verifiable;
+                                if ui
+                                    .checkbox(&mut verifiable, "Verifiable")
+                                    .on_hover_text("Winners are drawn with a seed whose hash can be announced before the draw.\nAfter the draw the proof can be copied from winners window.")
+                                    .changed()
+                                {
+                                    app.winners.set_verifiable(verifiable);
+                                }
+                                if app.winners.verifiable {
+                                    if let Some(ref commitment) = app.winners.commitment {
+                                        ui.vertical_centered_justified(|ui| {
+                                            if ui
+                                                .button("Copy seed hash")
+                                                .on_hover_text(&commitment.seed_hash)
+                                                .clicked()
+                                            {
+                                                ctx.copy_text(commitment.seed_hash.clone());
+                                                app.toasts.info("Seed hash copied!");
+                                            }
+                                        });
+                                    }
+                                }
+                            });
+                            ui.horizontal(|ui| {
+                                draw_changed |= ui.checkbox(&mut app.winners.weights.enabled, "Weighted")
+                                    .on_hover_text("Chances to win depend on weights instead of being equal.")
+                                    .changed();
+                                ui.add_enabled_ui(app.winners.weights.enabled, |ui| {
+                                    ui.style_mut().spacing.button_padding = [4.0, 2.0].into();
+                                    menu_custom_button(ui, Button::new("\u{2696} Weights").small(), |ui| {
+                                        ui.horizontal(|ui| {
+                                            ui.label("Has app in wishlist");
+                                            draw_changed |= ui.add(
+                                                DragValue::new(&mut app.winners.weights.wishlist_multiplier)
+                                                    .range(0.0..=100.0)
+                                                    .speed(0.1)
+                                                    .prefix("\u{D7} "),
+                                            )
+                                            .changed();
+                                        })
+                                        .response
+                                        .on_hover_text_at_pointer("Weight multiplier for who has store item for giveaway in wishlist");
+                                        ui.horizontal(|ui| {
+                                            ui.label("Each past win");
+                                            draw_changed |= ui.add(
+                                                DragValue::new(&mut app.winners.weights.past_win_multiplier)
+                                                    .range(0.0..=1.0)
+                                                    .speed(0.01)
+                                                    .prefix("\u{D7} "),
+                                            )
+                                            .changed();
+                                        })
+                                        .response
+                                        .on_hover_text_at_pointer("Weight multiplier applied for each time friend won before");
+                                        ui.horizontal(|ui| {
+                                            ui.label("Each account year");
+                                            draw_changed |= ui.add(
+                                                DragValue::new(&mut app.winners.weights.account_year_bonus)
+                                                    .range(0.0..=10.0)
+                                                    .speed(0.01)
+                                                    .prefix("+ "),
+                                            )
+                                            .changed();
+                                        })
+                                        .response
+                                        .on_hover_text_at_pointer("Weight added for each year of account age");
+                                        if ui.button("Reset").clicked() {
+                                            app.winners.weights = DrawWeights {
+                                                enabled: true,
+                                                ..Default::default()
+                                            };
+                                            draw_changed = true;
+                                        }
+                                    });
+                                });
+                            });
                         });
                     });
                 });
@@ -140,24 +272,8 @@ pub fn side_panel(app: &mut App, ctx: &Context) {
                                 app.preferences.store_items_capsules,
                             );
                             if r.changed {
-                                app.app_for_giveaway_user_details_is_loading = true;
-                                let steam = app.steam.clone();
-                                let sender = app.sender.clone();
-                                let app_id = app.store_item_for_giveaway.as_ref().unwrap().id;
-                                thread::spawn(move || {
-                                    let app_user_details = steam
-                                        .read()
-                                        .unwrap()
-                                        .app_user_details(&[app_id])
-                                        .unwrap()
-                                        .into_values()
-                                        .next()
-                                        .unwrap()
-                                        .unwrap();
-                                    let _ = sender.send(Msg::UpdateUserDetailsOfAppForGiveaway(
-                                        app_user_details,
-                                    ));
-                                });
+                                filters_changed = true;
+                                app.load_app_for_giveaway_user_details();
                             }
                         });
                     });
@@ -172,10 +288,75 @@ pub fn side_panel(app: &mut App, ctx: &Context) {
                             ui.heading("Filters");
                             if ui.button("Reset").clicked() {
                                 app.filters.reset(&app.friends);
+                                filters_changed = true;
+                            }
+                            if ui
+                                .button("\u{1F53B} Funnel")
+                                .on_hover_text("Friends left after each filter and why friend is excluded")
+                                .clicked()
+                            {
+                                app.show_funnel_window = true;
                             }
+                            ui.menu_button("\u{1F4BE}", |ui| {
+                                ui.horizontal(|ui| {
+                                    ui.add(
+                                        TextEdit::singleline(&mut app.filter_preset_name_buffer)
+                                            .char_limit(50)
+                                            .desired_width(120.)
+                                            .hint_text("Preset name..."),
+                                    );
+                                    let name = app.filter_preset_name_buffer.trim().to_owned();
+                                    if ui
+                                        .add_enabled(!name.is_empty(), Button::new("Save"))
+                                        .on_hover_text("Save current filters.\nPreset with the same name is overwritten.")
+                                        .clicked()
+                                    {
+                                        app.save_filter_preset(&name);
+                                    }
+                                });
+                                if !app.filter_presets.is_empty() {
+                                    ui.separator();
+                                }
+                                let mut load_index = None;
+                                let mut rename_index = None;
+                                let mut delete_index = None;
+                                for (index, preset) in app.filter_presets.iter().enumerate() {
+                                    ui.horizontal(|ui| {
+                                        if ui.button(&preset.name).on_hover_text("Load").clicked() {
+                                            load_index = Some(index);
+                                        }
+                                        if ui.small_button("\u{270F}").on_hover_text("Rename to the name above").clicked() {
+                                            rename_index = Some(index);
+                                        }
+                                        if ui.small_button("\u{1F5D1}").on_hover_text("Delete").clicked() {
+                                            delete_index = Some(index);
+                                        }
+                                    });
+                                }
+                                if let Some(index) = load_index {
+                                    app.load_filter_preset(index);
+                                    ui.close_menu();
+                                }
+                                if let Some(index) = rename_index {
+                                    let name = app.filter_preset_name_buffer.trim().to_owned();
+                                    if name.is_empty() {
+                                        app.toasts.error("Type new name first!");
+                                    } else if app.filter_presets.iter().any(|preset| preset.name == name) {
+                                        app.toasts.error(format!("Preset \"{name}\" already exists!"));
+                                    } else {
+                                        app.filter_presets[index].name = name;
+                                    }
+                                }
+                                if let Some(index) = delete_index {
+                                    let preset = app.filter_presets.remove(index);
+                                    app.toasts.info(format!("Preset \"{}\" deleted!", preset.name));
+                                }
+                            })
+                            .response
+                            .on_hover_text("Filter presets");
                         });
                         ui.horizontal_wrapped(|ui| {
-                            ui.add_enabled(
+                            filters_changed |= ui.add_enabled(
                                 app.store_item_for_giveaway
                                     .as_ref()
                                     .map(|app| app.user_details.is_some())
@@ -185,30 +366,216 @@ pub fn side_panel(app: &mut App, ctx: &Context) {
                                     "Only who has app in wishlist",
                                 ),
                             )
-                            .on_disabled_hover_text("User details is not loaded.");
+                            .on_disabled_hover_text("User details is not loaded.")
+                            .changed();
+                            filters_changed |= ui.add_enabled(
+                                app.store_item_for_giveaway
+                                    .as_ref()
+                                    .map(|app| app.user_details.is_some())
+                                    .unwrap_or_default(),
+                                Checkbox::new(
+                                    &mut app.filters.exclude_who_owns_app,
+                                    "Exclude who already owns app",
+                                ),
+                            )
+                            .on_disabled_hover_text("User details is not loaded.")
+                            .changed();
                             if app.app_for_giveaway_user_details_is_loading {
                                 ui.spinner()
                                     .on_hover_text_at_pointer("Downloading user details...");
                             }
                         });
-                        ui.checkbox(
+                        filters_changed |= ui.checkbox(
                             &mut app.filters.exclude_who_won_before,
                             "Exclude who won earlier",
-                        );
-                        ui.checkbox(
+                        )
+                        .changed();
+                        ui.add_enabled_ui(!app.filters.exclude_who_won_before, |ui| {
+                            ui.horizontal(|ui| {
+                                ui.label("Won in last");
+                                filters_changed |= ui.add(
+                                    DragValue::new(&mut app.filters.won_within_days)
+                                        .range(0..=3650)
+                                        .suffix(" d."),
+                                )
+                                .on_hover_text_at_pointer("Exclude who won in last days.\n0 to disable.")
+                                .changed();
+                                filters_changed |= ui.add(
+                                    DragValue::new(&mut app.filters.won_within_giveaways)
+                                        .range(0..=1000)
+                                        .suffix(" g."),
+                                )
+                                .on_hover_text_at_pointer("Exclude who won in last saved giveaways.\n0 to disable.")
+                                .changed();
+                            });
+                            ui.horizontal(|ui| {
+                                ui.label("Exclude who won");
+                                filters_changed |= ui.add(
+                                    DragValue::new(&mut app.filters.max_wins)
+                                        .range(0..=1000)
+                                        .prefix(">= ")
+                                        .suffix(" times"),
+                                )
+                                .on_hover_text_at_pointer("0 to disable.")
+                                .changed();
+                            });
+                        });
+                        filters_changed |= ui.checkbox(
                             &mut app.filters.exclude_unknown_age,
                             "Exclude unknown age",
-                        );
+                        )
+                        .changed();
                         ui.horizontal(|ui| {
                             ui.label("Account age");
-                            ui.add(
+                            filters_changed |= ui.add(
                                 DragValue::new(
                                     &mut app.filters.account_age
                                 )
                                 .range(0..=Utc::now().years_since(*STEAM_FOUNDATION_DATE).unwrap())
                                 .prefix(">= ")
-                            );
+                            )
+                            .changed();
+                        });
+                        ui.horizontal(|ui| {
+                            ui.label("Friends for");
+                            filters_changed |= ui.add(
+                                DragValue::new(&mut app.filters.friends_for_days)
+                                    .range(0..=9999)
+                                    .prefix(">= ")
+                                    .suffix(" days")
+                            )
+                            .on_hover_text_at_pointer("Excludes who became a friend recently.\n0 to disable.")
+                            .changed();
+                        });
+                        filters_changed |= ui.checkbox(
+                            &mut app.filters.steam_level.exclude_unknown,
+                            "Exclude unknown level",
+                        )
+                        .changed();
+                        ui.horizontal(|ui| {
+                            ui.label("Steam level");
+                            filters_changed |= bounds_ui(ui, &mut app.filters.steam_level.min, &mut app.filters.steam_level.max, MAX_STEAM_LEVEL, "");
+                        });
+                        filters_changed |= ui.checkbox(
+                            &mut app.filters.exclude_community_banned,
+                            "Exclude community banned",
+                        )
+                        .changed();
+                        filters_changed |= ui.checkbox(
+                            &mut app.filters.exclude_trade_banned,
+                            "Exclude trade banned",
+                        )
+                        .on_hover_text_at_pointer("Including who is on trade probation.")
+                        .changed();
+                        ui.horizontal(|ui| {
+                            ui.label("Exclude VAC banned in last");
+                            filters_changed |= ui.add(
+                                DragValue::new(&mut app.filters.vac_banned_within_days)
+                                    .range(0..=9999)
+                                    .suffix(" days")
+                            )
+                            .on_hover_text_at_pointer("0 to disable.")
+                            .changed();
+                        });
+                        filters_changed |= ui.checkbox(
+                            &mut app.filters.exclude_unknown_bans,
+                            "Exclude unknown bans",
+                        )
+                        .on_hover_text_at_pointer("Whose bans failed to load, they are not considered banned otherwise.")
+                        .changed();
+                        ui.horizontal(|ui| {
+                            ui.label("Online in last");
+                            filters_changed |= ui.add(
+                                DragValue::new(&mut app.filters.online_within_days)
+                                    .range(0..=9999)
+                                    .suffix(" days")
+                            )
+                            .on_hover_text_at_pointer("Who hides last online time is not excluded.\n0 to disable.")
+                            .changed();
+                        });
+                        let presence_is_fresh = app.friends.presence_is_fresh();
+                        ui.horizontal(|ui| {
+                            if !presence_is_fresh {
+                                ui.disable();
+                            }
+                            ui.label("Status");
+                            filters_changed |= ui.selectable_value(&mut app.filters.presence, PresenceFilter::Any, "Any").changed();
+                            filters_changed |= ui.selectable_value(&mut app.filters.presence, PresenceFilter::Online, "Online").changed();
+                            filters_changed |= ui.selectable_value(&mut app.filters.presence, PresenceFilter::InGame, "In game").changed();
+                        })
+                        .response
+                        .on_hover_text_at_pointer(if presence_is_fresh {
+                            "Status when friends were fetched, reload them to update."
+                        } else {
+                            "Status is outdated, reload friends to filter by it."
                         });
+                        let recent_activity = &mut app.filters.recent_activity;
+                        ui.horizontal(|ui| {
+                            if ui.checkbox(&mut recent_activity.enabled, "Played in last 2 weeks").changed() {
+                                filters_changed = true;
+                                if recent_activity.enabled {
+                                    app.friends.retry_failed_recent_playtime();
+                                }
+                            }
+                            filters_changed |= ui.add_enabled(
+                                recent_activity.enabled,
+                                DragValue::new(&mut recent_activity.min_hours)
+                                    .range(0..=336)
+                                    .prefix(">= ")
+                                    .suffix(" h.")
+                            )
+                            .on_hover_text_at_pointer("Total hours in all games.\n0 for any playtime.")
+                            .changed();
+                            if app.friends.recent_playtime_loading {
+                                ui.spinner().on_hover_text_at_pointer("Downloading recently played games...");
+                            }
+                        });
+                        if recent_activity.enabled {
+                            filters_changed |= ui.checkbox(&mut recent_activity.include_unknown, "Keep who hides games").changed();
+                            let unknown = app.friends.load_candidates
+                                .iter()
+                                .map(|&index| &app.friends.all[index])
+                                .filter(|friend| !matches!(
+                                    app.friends.recent_playtime.get(&friend.id),
+                                    Some(Some(RecentPlaytime::Minutes(_)))
+                                ))
+                                .count();
+                            if unknown > 0 && !app.friends.recent_playtime_loading {
+                                ui.horizontal(|ui| {
+                                    ui.label(format!("{} with unknown recent games", pluralize("friend", unknown)))
+                                        .on_hover_text_at_pointer("Due to privacy settings or failed requests.");
+                                    let failed = app.friends.recent_playtime.values().any(Option::is_none);
+                                    if failed && ui.button("\u{1F504}").on_hover_text_at_pointer("Retry failed requests").clicked() {
+                                        app.friends.retry_failed_recent_playtime();
+                                    }
+                                });
+                            }
+                        }
+                        CollapsingHeader::new("Filter groups")
+                            .show(ui, |ui| {
+                                ScrollArea::vertical().max_height(200.).show(ui, |ui| {
+                                    let mut editor = FilterTreeEditor {
+                                        search_select: &mut app.search_select,
+                                        steam: &app.steam,
+                                        sender: &app.sender,
+                                        app_user_details_token: app.filters_app_user_details_task.token(),
+                                        regions: &app.friends.regions,
+                                        country_code: app.steam
+                                            .read()
+                                            .unwrap()
+                                            .current_user
+                                            .as_ref()
+                                            .map(|u| u.country_code.clone())
+                                            .unwrap_or_default(),
+                                        with_capsules: app.preferences.store_items_capsules,
+                                        changed: false,
+                                    };
+                                    filter_node_ui(ui, &mut app.filters.tree, &mut editor, Id::new("filter_tree"), true);
+                                    filters_changed |= editor.changed;
+                                });
+                            })
+                            .header_response
+                            .on_hover_text("Combined with other filters by AND.\nFor example: (owns A OR wishlists B) AND NOT country RU");
                     });
                 });
 
@@ -235,10 +602,12 @@ pub fn side_panel(app: &mut App, ctx: &Context) {
                                             if ui.button("Include").clicked() {
                                                 app.filters.regions_and_countries.unknown =
                                                     RegionFilter::Include;
+                                                filters_changed = true;
                                             }
                                             if ui.button("Exclude").clicked() {
                                                 app.filters.regions_and_countries.unknown =
                                                     RegionFilter::Exclude;
+                                                filters_changed = true;
                                             }
                                         });
                                         ui.separator();
@@ -247,37 +616,26 @@ pub fn side_panel(app: &mut App, ctx: &Context) {
                                         ui.style().visuals.weak_text_color(),
                                         "Regions",
                                     );
-                                    let cis_available = matches!(
-                                        app.filters.regions_and_countries.cis,
-                                        RegionFilter::Available
-                                    );
-                                    if cis_available {
-                                        ui.menu_button("CIS", |ui| {
-                                            if ui.button("Include").clicked() {
-                                                app.filters.regions_and_countries.cis =
-                                                    RegionFilter::Include;
-                                            }
-                                            if ui.button("Exclude").clicked() {
-                                                app.filters.regions_and_countries.cis =
-                                                    RegionFilter::Exclude;
-                                            }
-                                        });
-                                    }
-                                    let eu_available = matches!(
-                                        app.filters.regions_and_countries.eu,
-                                        RegionFilter::Available
-                                    );
-                                    if eu_available {
-                                        ui.menu_button("EU", |ui| {
+                                    for group in &app.region_groups {
+                                        if app.filters.regions_and_countries.group_filter(&group.name) != RegionFilter::Available {
+                                            continue;
+                                        }
+                                        ui.menu_button(&group.name, |ui| {
                                             if ui.button("Include").clicked() {
-                                                app.filters.regions_and_countries.eu =
-                                                    RegionFilter::Include;
+                                                app.filters
+                                                    .regions_and_countries
+                                                    .set_group_filter(group, RegionFilter::Include);
+                                                filters_changed = true;
                                             }
                                             if ui.button("Exclude").clicked() {
-                                                app.filters.regions_and_countries.eu =
-                                                    RegionFilter::Exclude;
+                                                app.filters
+                                                    .regions_and_countries
+                                                    .set_group_filter(group, RegionFilter::Exclude);
+                                                filters_changed = true;
                                             }
-                                        });
+                                        })
+                                        .response
+                                        .on_hover_text(group.countries.join(", "));
                                     }
                                     ui.separator();
                                     ui.colored_label(
@@ -296,7 +654,8 @@ pub fn side_panel(app: &mut App, ctx: &Context) {
                                                         .regions_and_countries
                                                         .include_countries
                                                         .push(available_region.clone());
-                                                    retain = false
+                                                    retain = false;
+                                                    filters_changed = true;
                                                 }
                                                 if ui.button("Exclude").clicked() {
                                                     app.filters
@@ -304,14 +663,21 @@ pub fn side_panel(app: &mut App, ctx: &Context) {
                                                         .exclude_countries
                                                         .push(available_region.clone());
                                                     retain = false;
+                                                    filters_changed = true;
                                                 }
                                             });
                                             retain
                                         });
                                 });
                             });
+                            menu_custom_button(ui, Button::new("\u{270F}").small(), |ui| {
+                                region_groups_ui(ui, app);
+                            })
+                            .response
+                            .on_hover_text("Edit region groups");
                             if ui.small_button("Reset").clicked() {
                                 app.filters.reset_regions_and_countries(&app.friends);
+                                filters_changed = true;
                             }
                         });
                         ui.horizontal_top(|ui| {
@@ -348,44 +714,31 @@ pub fn side_panel(app: &mut App, ctx: &Context) {
                                                     if ui.small_button("\u{2796}").clicked() {
                                                         app.filters.regions_and_countries.unknown =
                                                             RegionFilter::Available;
+                                                        filters_changed = true;
                                                     }
                                                 });
                                             });
                                         }
-                                        if matches!(
-                                            app.filters.regions_and_countries.cis,
-                                            RegionFilter::Include
-                                        ) {
-                                            body.row(ROW_HEIGHT, |mut row| {
-                                                row.col(|ui| {
-                                                    ui.label("CIS");
-                                                });
-                                                row.col(|ui| {
-                                                    ui.style_mut().visuals.button_frame = false;
-                                                    if ui.small_button("\u{2796}").clicked() {
-                                                        app.filters.regions_and_countries.cis =
-                                                            RegionFilter::Available;
-                                                    }
-                                                });
-                                            });
-                                        }
-                                        if matches!(
-                                            app.filters.regions_and_countries.eu,
-                                            RegionFilter::Include
-                                        ) {
+                                        app.filters.regions_and_countries.groups.retain(|group_filter| {
+                                            if group_filter.filter != RegionFilter::Include {
+                                                return true;
+                                            }
+                                            let mut retain = true;
                                             body.row(ROW_HEIGHT, |mut row| {
                                                 row.col(|ui| {
-                                                    ui.label("EU");
+                                                    ui.label(&group_filter.group.name)
+                                                        .on_hover_text(group_filter.group.countries.join(", "));
                                                 });
                                                 row.col(|ui| {
                                                     ui.style_mut().visuals.button_frame = false;
                                                     if ui.small_button("\u{2796}").clicked() {
-                                                        app.filters.regions_and_countries.eu =
-                                                            RegionFilter::Available;
+                                                        retain = false;
+                                                        filters_changed = true;
                                                     }
                                                 });
                                             });
-                                        }
+                                            retain
+                                        });
                                         app.filters.regions_and_countries.include_countries.retain(
                                             |region_or_country| {
                                                 let mut retain = true;
@@ -401,6 +754,7 @@ pub fn side_panel(app: &mut App, ctx: &Context) {
                                                                 .available_countries
                                                                 .push(region_or_country.clone());
                                                             retain = false;
+                                                            filters_changed = true;
                                                         }
                                                     });
                                                 });
@@ -439,44 +793,31 @@ pub fn side_panel(app: &mut App, ctx: &Context) {
                                                     if ui.small_button("\u{2796}").clicked() {
                                                         app.filters.regions_and_countries.unknown =
                                                             RegionFilter::Available;
+                                                        filters_changed = true;
                                                     }
                                                 });
                                             });
                                         }
-                                        if matches!(
-                                            app.filters.regions_and_countries.cis,
-                                            RegionFilter::Exclude
-                                        ) {
-                                            body.row(ROW_HEIGHT, |mut row| {
-                                                row.col(|ui| {
-                                                    ui.label("CIS");
-                                                });
-                                                row.col(|ui| {
-                                                    ui.style_mut().visuals.button_frame = false;
-                                                    if ui.small_button("\u{2796}").clicked() {
-                                                        app.filters.regions_and_countries.cis =
-                                                            RegionFilter::Available;
-                                                    }
-                                                });
-                                            });
-                                        }
-                                        if matches!(
-                                            app.filters.regions_and_countries.eu,
-                                            RegionFilter::Exclude
-                                        ) {
+                                        app.filters.regions_and_countries.groups.retain(|group_filter| {
+                                            if group_filter.filter != RegionFilter::Exclude {
+                                                return true;
+                                            }
+                                            let mut retain = true;
                                             body.row(ROW_HEIGHT, |mut row| {
                                                 row.col(|ui| {
-                                                    ui.label("EU");
+                                                    ui.label(&group_filter.group.name)
+                                                        .on_hover_text(group_filter.group.countries.join(", "));
                                                 });
                                                 row.col(|ui| {
                                                     ui.style_mut().visuals.button_frame = false;
                                                     if ui.small_button("\u{2796}").clicked() {
-                                                        app.filters.regions_and_countries.eu =
-                                                            RegionFilter::Available;
+                                                        retain = false;
+                                                        filters_changed = true;
                                                     }
                                                 });
                                             });
-                                        }
+                                            retain
+                                        });
                                         app.filters.regions_and_countries.exclude_countries.retain(
                                             |region_or_country| {
                                                 let mut retain = true;
@@ -492,6 +833,7 @@ pub fn side_panel(app: &mut App, ctx: &Context) {
                                                                 .available_countries
                                                                 .push(region_or_country.clone());
                                                             retain = false;
+                                                            filters_changed = true;
                                                         }
                                                     });
                                                 });
@@ -513,9 +855,11 @@ pub fn side_panel(app: &mut App, ctx: &Context) {
                                 ui.label("Has store items");
                                 if ui.small_button("\u{2795}").clicked() {
                                     app.filters.has_store_items.push(Default::default());
+                                    filters_changed = true;
                                 }
                                 if ui.small_button("Reset").clicked() {
                                     app.filters.has_store_items.clear();
+                                    filters_changed = true;
                                 }
                             });
                             let table_height = ui.available_height() - 25.;
@@ -525,12 +869,15 @@ pub fn side_panel(app: &mut App, ctx: &Context) {
                                 .stick_to_bottom(true)
                                 .min_scrolled_height(table_height)
                                 .max_scroll_height(table_height)
+                                .column(Column::exact(20.))
                                 .column(Column::exact(50.))
-                                .column(Column::exact(80.))
-                                .column(Column::exact(65.))
+                                .column(Column::exact(60.))
+                                .column(Column::exact(50.))
+                                .column(Column::exact(35.))
                                 .column(Column::exact(20.))
                                 .cell_layout(Layout::centered_and_justified(Direction::LeftToRight))
                                 .header(22., |mut row| {
+                                    row.col(|_| {});
                                     row.col(|ui| {
                                         ui.label("Item");
                                     });
@@ -540,12 +887,31 @@ pub fn side_panel(app: &mut App, ctx: &Context) {
                                     row.col(|ui| {
                                         ui.label("\u{23F3} 2 weeks").on_hover_text("Playtime for last 2 weeks");
                                     });
+                                    row.col(|ui| {
+                                        ui.label("\u{1F3C6}").on_hover_text("Percent of unlocked achievements");
+                                    });
                                 })
                                 .body(|mut body| {
+                                    let len = app.filters.has_store_items.len();
                                     app.filters.has_store_items.dedup();
+                                    filters_changed |= app.filters.has_store_items.len() != len;
                                     app.filters.has_store_items.retain_mut(|filter| {
                                         let mut retain = true;
                                         body.row(18., |mut row| {
+                                            row.col(|ui| {
+                                                ui.style_mut().visuals.button_frame = false;
+                                                let (text, hover_text) = match filter.mode {
+                                                    HasAppMode::Owns => ("\u{2714}", "Owns.\nClick to switch to does not own."),
+                                                    HasAppMode::NotOwns => ("\u{2716}", "Does not own or hides it with privacy settings.\nClick to switch to owns."),
+                                                };
+                                                if ui.small_button(text).on_hover_text_at_pointer(hover_text).clicked() {
+                                                    filter.mode = match filter.mode {
+                                                        HasAppMode::Owns => HasAppMode::NotOwns,
+                                                        HasAppMode::NotOwns => HasAppMode::Owns,
+                                                    };
+                                                    filters_changed = true;
+                                                }
+                                            });
                                             row.col(|ui| {
                                                 // ui.set_max_height(ui.available_height() - 2.);
                                                 if filter.is_loading {
@@ -566,29 +932,46 @@ pub fn side_panel(app: &mut App, ctx: &Context) {
                                                     app.preferences.store_items_capsules,
                                                 );
                                                 if select_app_response.changed {
+                                                    filters_changed = true;
                                                     filter.is_loading = true;
-                                                    let steam = app.steam.clone();
-                                                    let sender = app.sender.clone();
-                                                    let app_id = filter.app.as_ref().unwrap().id;
-                                                    thread::spawn(move || {
-                                                        if let Ok(mut apps_user_details) = steam.read().unwrap().app_user_details(&[app_id]) {
-                                                            if let Some(app_user_details) = apps_user_details.get_mut(&app_id).map(|opt| opt.take()).unwrap_or_default() {
-                                                                let _ = sender.send(Msg::UpdateUserDetailsOfHasAppFilter(app_id, app_user_details));
-                                                            }
-                                                        }
-                                                    });
+                                                    load_filter_app_user_details(app.steam.clone(), app.sender.clone(), app.filters_app_user_details_task.token(), filter.app.as_ref().unwrap().id);
                                                 }
                                             });
+                                            let playtime_enabled = matches!(filter.mode, HasAppMode::Owns);
                                             row.col(|ui| {
-                                                ui.add(DragValue::new(&mut filter.playtime_total).range(0..=999_999).prefix(">= ").suffix(" h."));
+                                                ui.add_enabled_ui(playtime_enabled, |ui| {
+                                                    filters_changed |= playtime_bounds_ui(ui, &mut filter.playtime_total, &mut filter.max_playtime_total, 999_999);
+                                                });
                                             });
                                             row.col(|ui| {
-                                                ui.add(DragValue::new(&mut filter.playtime_twoweeks).range(0..=336).prefix(">= ").suffix(" h."));
+                                                ui.add_enabled_ui(playtime_enabled, |ui| {
+                                                    filters_changed |= playtime_bounds_ui(ui, &mut filter.playtime_twoweeks, &mut filter.max_playtime_twoweeks, 336);
+                                                });
+                                            });
+                                            row.col(|ui| {
+                                                if filter.achievement_percent > 0
+                                                    && filter.app.as_ref().is_some_and(|item| app.friends.achievements_loading.contains(&item.id))
+                                                {
+                                                    ui.spinner().on_hover_text_at_pointer("Downloading achievements...");
+                                                    return;
+                                                }
+                                                ui.add_enabled_ui(playtime_enabled, |ui| {
+                                                    ui.menu_button(format!("{}%", filter.achievement_percent), |ui| {
+                                                        ui.horizontal(|ui| {
+                                                            ui.label("At least");
+                                                            filters_changed |= ui.add(DragValue::new(&mut filter.achievement_percent).range(0..=100).suffix("%")).changed();
+                                                        });
+                                                        filters_changed |= ui.checkbox(&mut filter.include_private_achievements, "Include who hides achievements")
+                                                            .on_hover_text("Achievements can be hidden by privacy settings or be unavailable")
+                                                            .changed();
+                                                    });
+                                                });
                                             });
                                             row.col(|ui| {
                                                 ui.style_mut().visuals.button_frame = false;
                                                 if ui.small_button("\u{2796}").clicked() {
                                                     retain = false;
+                                                    filters_changed = true;
                                                 }
                                                 ui.add_space(5.);
                                             });
@@ -598,4 +981,368 @@ pub fn side_panel(app: &mut App, ctx: &Context) {
                                 })
                     });
         });
+    if filters_changed {
+        app.filters_revision += 1;
+    }
+    if draw_changed {
+        app.winners.revision += 1;
+    }
+}
+
+/// Min (inclusive) and optional max (exclusive) playtime in hours.
+/// Returns `true` if any of them changed.
+#[inline]
+fn playtime_bounds_ui<T: Numeric + Display>(ui: &mut Ui, min: &mut T, max: &mut Option<T>, limit: T) -> bool {
+    bounds_ui(ui, min, max, limit, " h.")
+}
+
+/// Min (inclusive) and optional max (exclusive) values with unit suffix.
+/// Returns `true` if any of them changed.
+fn bounds_ui<T: Numeric + Display>(ui: &mut Ui, min: &mut T, max: &mut Option<T>, limit: T, suffix: &str) -> bool {
+    let mut changed = false;
+    let text = match max {
+        Some(max) if min.to_f64() == 0. => format!("< {max}{suffix}"),
+        Some(max) => format!("{min}-{max}{suffix}"),
+        None => format!(">= {min}{suffix}"),
+    };
+    ui.menu_button(text, |ui| {
+        ui.horizontal(|ui| {
+            ui.label("At least");
+            changed |= ui.add(DragValue::new(min).range(T::from_f64(0.)..=limit).suffix(suffix)).changed();
+        });
+        ui.horizontal(|ui| {
+            let mut has_max = max.is_some();
+            if ui.checkbox(&mut has_max, "Less than").changed() {
+                *max = has_max.then(|| T::from_f64((min.to_f64() + 1.).min(limit.to_f64())));
+                changed = true;
+            }
+            if let Some(max) = max {
+                changed |= ui.add(DragValue::new(max).range(T::from_f64(1.)..=limit).suffix(suffix)).changed();
+            }
+        });
+    });
+    changed
+}
+
+const MAX_STEAM_LEVEL: u32 = 5000;
+
+struct FilterTreeEditor<'a> {
+    search_select: &'a mut SearchSelect,
+    steam: &'a Arc<RwLock<SteamApiClient>>,
+    sender: &'a Sender<Msg>,
+    app_user_details_token: TaskToken,
+    regions: &'a [String],
+    country_code: Option<String>,
+    with_capsules: bool,
+    /// Set when any node is edited
+    changed: bool,
+}
+
+fn filter_app_select_ui(ui: &mut Ui, app: &mut Option<SteamStoreItem>, is_loading: &mut bool, editor: &mut FilterTreeEditor) {
+    if *is_loading {
+        ui.spinner().on_hover_text_at_pointer("Downloading user details...");
+        return;
+    }
+    ui.allocate_ui([80., 18.].into(), |ui| {
+        if editor.search_select.show(ui, editor.steam.clone(), app, editor.country_code.clone(), editor.with_capsules).changed {
+            editor.changed = true;
+            *is_loading = true;
+            load_filter_app_user_details(editor.steam.clone(), editor.sender.clone(), editor.app_user_details_token.clone(), app.as_ref().unwrap().id);
+        }
+    });
+}
+
+/// Returns `true` if node should be removed
+fn filter_node_ui(ui: &mut Ui, node: &mut FilterNode, editor: &mut FilterTreeEditor, id: Id, is_root: bool) -> bool {
+    let mut remove = false;
+    ui.horizontal(|ui| {
+        editor.changed |= ui.toggle_value(&mut node.negate, "NOT").on_hover_text("Negate").changed();
+        match node.kind {
+            FilterNodeKind::Group { ref mut operator, ref mut children } => {
+                if ui.small_button(operator.to_string()).on_hover_text("Click to switch AND/OR").clicked() {
+                    *operator = match operator {
+                        GroupOperator::And => GroupOperator::Or,
+                        GroupOperator::Or => GroupOperator::And,
+                    };
+                    editor.changed = true;
+                }
+                ui.menu_button("\u{2795}", |ui| {
+                    let kinds = [
+                        ("Group", FilterNodeKind::default()),
+                        ("Country", FilterNodeKind::Country(Default::default())),
+                        ("Account age", FilterNodeKind::AccountAge(Default::default())),
+                        ("Friends for", FilterNodeKind::FriendsSince(Default::default())),
+                        ("Steam level", FilterNodeKind::SteamLevel(Default::default())),
+                        ("Won before", FilterNodeKind::WonBefore),
+                        ("Has app", FilterNodeKind::HasApp(Default::default())),
+                        ("Has app in wishlist", FilterNodeKind::InWishlist(Default::default())),
+                        ("Community banned", FilterNodeKind::CommunityBanned),
+                        ("Trade banned", FilterNodeKind::TradeBanned),
+                        ("VAC banned", FilterNodeKind::VacBannedWithin(VacBannedWithin(365))),
+                        ("Online", FilterNodeKind::OnlineWithin(OnlineWithin(7))),
+                        ("Status", FilterNodeKind::Presence(PresenceFilter::Online)),
+                        ("Played recently", FilterNodeKind::RecentActivity(RecentActivityFilter {
+                            enabled: true,
+                            ..Default::default()
+                        })),
+                    ];
+                    for (text, kind) in kinds {
+                        if ui.button(text).clicked() {
+                            children.push(FilterNode::new(kind));
+                            editor.changed = true;
+                            ui.close_menu();
+                        }
+                    }
+                });
+            }
+            FilterNodeKind::Country(CountryFilter(ref mut country)) => {
+                ui.label("Country");
+                ComboBox::from_id_salt(id)
+                    .selected_text(country.as_deref().unwrap_or("Unknown"))
+                    .width(60.)
+                    .show_ui(ui, |ui| {
+                        editor.changed |= ui.selectable_value(country, None, "Unknown").changed();
+                        for region in editor.regions {
+                            editor.changed |= ui.selectable_value(country, Some(region.clone()), region).changed();
+                        }
+                    });
+            }
+            FilterNodeKind::AccountAge(ref mut filter) => {
+                ui.label("Account age");
+                editor.changed |= ui
+                    .add(
+                        DragValue::new(&mut filter.min_years)
+                            .range(0..=Utc::now().years_since(*STEAM_FOUNDATION_DATE).unwrap())
+                            .prefix(">= ")
+                    )
+                    .changed();
+            }
+            FilterNodeKind::FriendsSince(ref mut filter) => {
+                ui.label("Friends for");
+                editor.changed |= ui
+                    .add(
+                        DragValue::new(&mut filter.min_days)
+                            .range(0..=9999)
+                            .prefix(">= ")
+                            .suffix(" days")
+                    )
+                    .changed();
+            }
+            FilterNodeKind::SteamLevel(ref mut filter) => {
+                ui.label("Steam level");
+                editor.changed |= bounds_ui(ui, &mut filter.min, &mut filter.max, MAX_STEAM_LEVEL, "");
+            }
+            FilterNodeKind::WonBefore => {
+                ui.label("Won before");
+            }
+            FilterNodeKind::HasApp(ref mut filter) => {
+                ui.style_mut().visuals.button_frame = false;
+                let (text, hover_text) = match filter.mode {
+                    HasAppMode::Owns => ("\u{2714}", "Owns.\nClick to switch to does not own."),
+                    HasAppMode::NotOwns => ("\u{2716}", "Does not own or hides it with privacy settings.\nClick to switch to owns."),
+                };
+                if ui.small_button(text).on_hover_text_at_pointer(hover_text).clicked() {
+                    filter.mode = match filter.mode {
+                        HasAppMode::Owns => HasAppMode::NotOwns,
+                        HasAppMode::NotOwns => HasAppMode::Owns,
+                    };
+                    editor.changed = true;
+                }
+                ui.style_mut().visuals.button_frame = true;
+                filter_app_select_ui(ui, &mut filter.app, &mut filter.is_loading, editor);
+                if matches!(filter.mode, HasAppMode::Owns) {
+                    editor.changed |= playtime_bounds_ui(ui, &mut filter.playtime_total, &mut filter.max_playtime_total, 999_999);
+                }
+            }
+            FilterNodeKind::InWishlist(ref mut filter) => {
+                ui.label("Wishlists");
+                filter_app_select_ui(ui, &mut filter.app, &mut filter.is_loading, editor);
+            }
+            FilterNodeKind::CommunityBanned => {
+                ui.label("Community banned");
+            }
+            FilterNodeKind::TradeBanned => {
+                ui.label("Trade banned");
+            }
+            FilterNodeKind::VacBannedWithin(VacBannedWithin(ref mut days)) => {
+                ui.label("VAC banned in last");
+                editor.changed |= ui
+                    .add(DragValue::new(days).range(0..=9999).suffix(" days"))
+                    .on_hover_text_at_pointer("0 to disable.")
+                    .changed();
+            }
+            FilterNodeKind::OnlineWithin(OnlineWithin(ref mut days)) => {
+                ui.label("Online in last");
+                editor.changed |= ui
+                    .add(DragValue::new(days).range(0..=9999).suffix(" days"))
+                    .on_hover_text_at_pointer("Who hides last online time passes.\n0 to disable.")
+                    .changed();
+            }
+            FilterNodeKind::Presence(ref mut filter) => {
+                ui.label("Status");
+                ComboBox::from_id_salt(id)
+                    .selected_text(filter.name())
+                    .width(60.)
+                    .show_ui(ui, |ui| {
+                        editor.changed |= ui.selectable_value(filter, PresenceFilter::Any, "Any").changed();
+                        editor.changed |= ui.selectable_value(filter, PresenceFilter::Online, "Online").changed();
+                        editor.changed |= ui.selectable_value(filter, PresenceFilter::InGame, "In game").changed();
+                    })
+                    .response
+                    .on_hover_text_at_pointer("Skipped if status is outdated, reload friends to update it.");
+            }
+            FilterNodeKind::RecentActivity(ref mut filter) => {
+                ui.label("Played");
+                editor.changed |= ui
+                    .add(
+                        DragValue::new(&mut filter.min_hours)
+                            .range(0..=336)
+                            .prefix(">= ")
+                            .suffix(" h.")
+                    )
+                    .on_hover_text_at_pointer("Total hours in all games in last 2 weeks.\n0 for any playtime.")
+                    .changed();
+                editor.changed |= ui
+                    .checkbox(&mut filter.include_unknown, "")
+                    .on_hover_text_at_pointer("Keep who hides games")
+                    .changed();
+            }
+        }
+        if !is_root && ui.small_button("\u{2796}").clicked() {
+            remove = true;
+            editor.changed = true;
+        }
+    });
+    if let FilterNodeKind::Group { ref mut children, .. } = node.kind {
+        ui.indent(id, |ui| {
+            let mut index = 0;
+            children.retain_mut(|child| {
+                let retain = !filter_node_ui(ui, child, editor, id.with(index), false);
+                index += 1;
+                retain
+            });
+        });
+    }
+    remove
+}
+
+/// Editor of user-defined region groups
+fn region_groups_ui(ui: &mut Ui, app: &mut App) {
+    ScrollArea::vertical().max_height(300.).show(ui, |ui| {
+        let mut rename_index = None;
+        let mut delete_index = None;
+        for (index, group) in app.region_groups.iter_mut().enumerate() {
+            let mut changed = false;
+            let is_edited = app.edited_region_group == Some(index);
+            let header = CollapsingHeader::new(format!("{} ({})", group.name, group.countries.len()))
+                .id_salt(("region_group", index))
+                .open(Some(is_edited))
+                .show(ui, |ui| {
+                    ui.horizontal(|ui| {
+                        ui.add(
+                            TextEdit::singleline(&mut app.region_group_name_buffer)
+                                .char_limit(30)
+                                .desired_width(120.)
+                                .hint_text("New name..."),
+                        );
+                        if ui.small_button("\u{270F}").on_hover_text("Rename to the name on the left").clicked() {
+                            rename_index = Some(index);
+                        }
+                        if ui.small_button("\u{1F5D1}").on_hover_text("Delete group").clicked() {
+                            delete_index = Some(index);
+                        }
+                    });
+                    ui.horizontal_wrapped(|ui| {
+                        group.countries.retain(|country| {
+                            let retain = !ui
+                                .small_button(format!("{country} \u{2716}"))
+                                .on_hover_text("Remove")
+                                .clicked();
+                            changed |= !retain;
+                            retain
+                        });
+                    });
+                    ui.horizontal(|ui| {
+                        ui.add(
+                            TextEdit::singleline(&mut app.region_group_country_buffer)
+                                .char_limit(2)
+                                .desired_width(30.)
+                                .hint_text("RU"),
+                        );
+                        let country = app.region_group_country_buffer.trim().to_uppercase();
+                        if ui
+                            .add_enabled(country.len() == 2 && !group.contains(&country), Button::new("Add"))
+                            .clicked()
+                        {
+                            group.countries.push(country);
+                            app.region_group_country_buffer.clear();
+                            changed = true;
+                        }
+                        ui.menu_button("Friends regions", |ui| {
+                            ScrollArea::vertical().max_height(200.).show(ui, |ui| {
+                                for region in &app.friends.regions {
+                                    if !group.contains(region) && ui.button(region).clicked() {
+                                        group.countries.push(region.clone());
+                                        changed = true;
+                                    }
+                                }
+                            });
+                        });
+                    });
+                });
+            // buffers are shared, so only one group is open for editing
+            if header.header_response.clicked() {
+                app.edited_region_group = (!is_edited).then_some(index);
+                app.region_group_name_buffer.clear();
+                app.region_group_country_buffer.clear();
+            }
+            if changed {
+                app.filters.regions_and_countries.update_group(&group.name, Some(group));
+                app.filters_revision += 1;
+            }
+        }
+        if let Some(index) = rename_index {
+            let name = app.region_group_name_buffer.trim().to_owned();
+            if name.is_empty() {
+                app.toasts.error("Type new name first!");
+            } else if app.region_groups.iter().any(|group| group.name == name) {
+                app.toasts.error(format!("Group \"{name}\" already exists!"));
+            } else {
+                let group = &mut app.region_groups[index];
+                let old_name = std::mem::replace(&mut group.name, name);
+                app.filters.regions_and_countries.update_group(&old_name, Some(group));
+                app.filters_revision += 1;
+                app.region_group_name_buffer.clear();
+            }
+        }
+        if let Some(index) = delete_index {
+            let group = app.region_groups.remove(index);
+            app.edited_region_group = None;
+            app.filters.regions_and_countries.update_group(&group.name, None);
+            app.filters_revision += 1;
+        }
+        ui.separator();
+        ui.horizontal(|ui| {
+            if ui.button("\u{2795} New group").clicked() {
+                let name = (1..)
+                    .map(|n| format!("Group {n}"))
+                    .find(|name| !app.region_groups.iter().any(|group| &group.name == name))
+                    .unwrap();
+                app.region_groups.push(RegionGroup::new(&name, &[]));
+                app.edited_region_group = Some(app.region_groups.len() - 1);
+                app.region_group_name_buffer.clear();
+                app.region_group_country_buffer.clear();
+            }
+            if ui
+                .button("Restore defaults")
+                .on_hover_text("Restores built-in groups and their countries.\nOther groups are kept.")
+                .clicked()
+            {
+                RegionGroup::restore_defaults(&mut app.region_groups);
+                app.edited_region_group = None;
+                app.filters.regions_and_countries.sync_groups(&app.region_groups);
+                app.filters_revision += 1;
+            }
+        });
+    });
 }
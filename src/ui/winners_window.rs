@@ -1,7 +1,7 @@
-use eframe::egui::{Align, Button, Context, Layout, OpenUrl, Window};
+use eframe::egui::{Align, Button, Context, Layout, OpenUrl, TextEdit, Window};
 use egui_extras::{Column, TableBuilder};
 
-use crate::app::App;
+use crate::app::{App, DrawProof, DrawProofError, RerollReason};
 
 pub fn winners_window(app: &mut App, ctx: &Context) {
     Window::new(format!(
@@ -11,6 +11,57 @@ pub fn winners_window(app: &mut App, ctx: &Context) {
     .open(&mut app.show_winners_window)
     .order(eframe::egui::Order::Middle)
     .show(ctx, |ui| {
+        if let Some(ref proof) = app.winners.proof {
+            ui.horizontal(|ui| {
+                ui.label("Seed hash:");
+                ui.monospace(&proof.seed_hash[..16])
+                    .on_hover_text_at_pointer(&proof.seed_hash);
+                if ui
+                    .button("\u{1F4CB} Copy proof")
+                    .on_hover_text_at_pointer(
+                        "Copy seed, candidates and winners.\nAnyone can re-run the draw with it.",
+                    )
+                    .clicked()
+                {
+                    ctx.copy_text(serde_json::to_string_pretty(proof).unwrap());
+                    app.toasts.info("Draw proof copied!");
+                }
+            });
+        }
+        ui.collapsing("Verify draw proof", |ui| {
+            ui.add(
+                TextEdit::multiline(&mut app.draw_proof_buffer)
+                    .desired_rows(3)
+                    .desired_width(f32::INFINITY)
+                    .hint_text("Paste draw proof here..."),
+            );
+            if ui.button("Verify").clicked() {
+                match serde_json::from_str::<DrawProof>(&app.draw_proof_buffer) {
+                    Ok(proof) => match proof.verify() {
+                        Ok(()) => {
+                            app.toasts.success("Draw proof is valid!");
+                        }
+                        Err(err) => {
+                            app.toasts.error(match err {
+                                DrawProofError::InvalidSeed => "Seed is invalid!",
+                                DrawProofError::InvalidWeights => "Weights are invalid!",
+                                DrawProofError::SeedHashMismatch => "Seed does not match its hash!",
+                                DrawProofError::CandidatesHashMismatch => {
+                                    "Candidates do not match their hash!"
+                                }
+                                DrawProofError::WinnersMismatch => {
+                                    "Winners do not match the draw result!"
+                                }
+                            });
+                        }
+                    },
+                    Err(_) => {
+                        app.toasts.error("Draw proof is malformed!");
+                    }
+                }
+            }
+        });
+        let mut reroll = None;
         let mut table = TableBuilder::new(ui).striped(true);
         if app.preferences.avatars {
             table = table.column(Column::auto().at_least(40.));
@@ -21,6 +72,9 @@ pub fn winners_window(app: &mut App, ctx: &Context) {
             .column(Column::auto())
             .column(Column::auto())
             .column(Column::auto())
+            .column(Column::auto())
+            .column(Column::auto())
+            .column(Column::auto())
             .cell_layout(Layout::left_to_right(Align::Center))
             .header(20.0, |mut header| {
                 if app.preferences.avatars {
@@ -44,16 +98,36 @@ pub fn winners_window(app: &mut App, ctx: &Context) {
                         .on_hover_text_at_pointer("How old is the account in years");
                     ui.add_space(10.);
                 });
+                header.col(|ui| {
+                    ui.heading("\u{2B50} Level")
+                        .on_hover_text_at_pointer("Steam level");
+                });
+                header.col(|ui| {
+                    ui.heading("\u{1F91D} Since")
+                        .on_hover_text_at_pointer("Since when you are friends");
+                });
                 header.col(|ui| {
                     ui.heading("\u{1F3C6} Won");
                 });
             })
             .body(|body| {
+                let presence_is_fresh = app.friends.presence_is_fresh();
                 if !app.winners.current.is_empty() {
                     body.rows(32., app.winners.current.len(), |mut row| {
-                        let friend = &app.winners.current[row.index()];
-                        friend.add_to_table_row(&app.preferences, &app.winners, ctx, &mut row);
+                        let index = row.index();
+                        let friend = &app.winners.current[index];
+                        friend.add_to_table_row(&app.preferences, &app.winners, presence_is_fresh, ctx, &mut row);
                         row.col(|ui| {
+                            if app
+                                .store_item_for_giveaway
+                                .as_ref()
+                                .and_then(|app| app.user_details.as_ref())
+                                .map(|details| details.is_owned_by(friend.id))
+                                .unwrap_or_default()
+                            {
+                                ui.colored_label(ui.style().visuals.warn_fg_color, "\u{26A0}")
+                                    .on_hover_text_at_pointer("Already owns app for giveaway!");
+                            }
                             let app_for_giveaway_is_some = app.store_item_for_giveaway.is_some();
                             if ui
                                 .add_enabled(
@@ -75,8 +149,46 @@ pub fn winners_window(app: &mut App, ctx: &Context) {
                                 )));
                             }
                         });
+                        row.col(|ui| {
+                            ui.menu_button("\u{1F504}", |ui| {
+                                ui.label("Re-roll because of");
+                                for reason in RerollReason::ALL {
+                                    if ui.button(reason.to_string()).clicked() {
+                                        reroll = Some((index, reason));
+                                        ui.close_menu();
+                                    }
+                                }
+                            })
+                            .response
+                            .on_hover_text_at_pointer("Replace only this winner with a new one");
+                        });
                     });
                 }
             });
+
+        if let Some((index, reason)) = reroll {
+            if !app.winners.reroll(
+                index,
+                reason,
+                &app.friends,
+                app.store_item_for_giveaway.as_ref(),
+            ) {
+                app.toasts.error("Nobody left to re-roll!");
+            }
+        }
+
+        if !app.winners.current_replaced.is_empty() {
+            ui.collapsing(
+                format!("Replaced ({})", app.winners.current_replaced.len()),
+                |ui| {
+                    for replaced in &app.winners.current_replaced {
+                        ui.label(format!(
+                            "{} \u{2014} {}",
+                            replaced.winner.name, replaced.reason
+                        ));
+                    }
+                },
+            );
+        }
     });
 }
@@ -1,5 +1,6 @@
 use std::io::Cursor;
 
+use chrono::TimeDelta;
 use eframe::egui::{IconData, Ui};
 use image::ImageReader;
 
@@ -33,3 +34,16 @@ pub fn icon_from_bytes(bytes: &[u8]) -> Option<IconData> {
         height,
     })
 }
+
+/// Age like `5 min. ago`, `3 h. ago` or `2 d. ago`
+pub fn format_age(age: TimeDelta) -> String {
+    if age.num_days() > 0 {
+        format!("{} d. ago", age.num_days())
+    } else if age.num_hours() > 0 {
+        format!("{} h. ago", age.num_hours())
+    } else if age.num_minutes() > 0 {
+        format!("{} min. ago", age.num_minutes())
+    } else {
+        "just now".to_owned()
+    }
+}